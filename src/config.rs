@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::utils::config::{ConfigPath, JsonConfig};
+use crate::utils::{
+    config::{ConfigPath, JsonConfig},
+    maintenance::MaintenanceConfig,
+};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub discord_webhook_url: String,
     pub networks: HashMap<String, Network>,
+    /// Latest release endpoint of GitHub releases API (or compatible), used by `self-update`
+    pub release_feed_url: reqwest::Url,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -18,6 +25,9 @@ pub struct Network {
     pub chainspec: String,
     pub explorer_url: String,
     pub name: String,
+    /// Expected chain ID of the network chainspec
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
 }
 
 impl JsonConfig for Config {
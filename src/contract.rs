@@ -4,6 +4,24 @@ use alloy::{
 };
 use anyhow::anyhow;
 use serde::{de, Deserialize};
+use std::collections::HashMap;
+
+use crate::error::AppError;
+
+const DEPLOYMENTS_JSON: [(u64, &str); 3] = [
+    (
+        22040,
+        include_str!("../airdao-node-contracts/deployments/22040.json"),
+    ),
+    (
+        16718,
+        include_str!("../airdao-node-contracts/deployments/16718.json"),
+    ),
+    (
+        30746,
+        include_str!("../airdao-node-contracts/deployments/30746.json"),
+    ),
+];
 
 #[derive(Debug)]
 pub struct EthContract {
@@ -20,6 +38,21 @@ impl EthContract {
     }
 }
 
+/// Contracts deployed to the chain, by name
+pub fn deployment(chain_id: u64) -> Result<HashMap<String, EthContract>, AppError> {
+    let (_, json_text) = DEPLOYMENTS_JSON
+        .iter()
+        .find(|(deployment_chain_id, _)| *deployment_chain_id == chain_id)
+        .ok_or_else(|| {
+            anyhow!(
+                "Unable to find deployment information for chain id `{}`",
+                chain_id
+            )
+        })?;
+
+    serde_json::from_str(json_text).map_err(AppError::from)
+}
+
 impl<'de> Deserialize<'de> for EthContract {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
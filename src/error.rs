@@ -23,6 +23,18 @@ pub enum AppError {
     /// YAML reader error
     #[error("YAML scan error: {0}")]
     YamlScan(#[from] yaml_rust2::ScanError),
+    /// YAML writer error
+    #[error("YAML emit error: {0}")]
+    YamlEmit(#[from] yaml_rust2::EmitError),
+    /// TOML reader error
+    #[error("TOML parse error: {0}")]
+    TomlDe(#[from] toml::de::Error),
+    /// TOML writer error
+    #[error("TOML serialize error: {0}")]
+    TomlSer(#[from] toml::ser::Error),
+    /// Zip archive error
+    #[error("Zip archive error: {0}")]
+    Zip(#[from] zip::result::ZipError),
     /// Keystore error
     #[error("Keystore error: {0}")]
     Keystore(#[from] eth_keystore::KeystoreError),
@@ -47,7 +59,46 @@ pub enum AppError {
     /// Url parse error
     #[error("Url parse error: {0}")]
     UrlParse(#[from] url::ParseError),
+    /// Template rendering error
+    #[error("Template error: {0:#}")]
+    Template(#[from] minijinja::Error),
+    /// Prompt is needed, but the output is not interactive
+    #[error(
+        "Input required: {0} must be passed with command line options when prompts are disabled"
+    )]
+    InputRequired(String),
     /// Generic
     #[error("{0:#}")]
     Anyhow(#[from] anyhow::Error),
 }
+
+impl AppError {
+    /// Stable error code reported in machine readable output
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::IO(_) => "io",
+            Self::FromHex(_) => "hex_decode",
+            Self::Signature(_) => "signature",
+            Self::IpAddress(_) => "ip_address",
+            Self::Reqwest(_) => "http",
+            Self::SerdeJson(_) => "json",
+            Self::Utf8(_) => "utf8",
+            Self::YamlScan(_) => "yaml_parse",
+            Self::YamlEmit(_) => "yaml_emit",
+            Self::TomlDe(_) => "toml_parse",
+            Self::TomlSer(_) => "toml_serialize",
+            Self::Zip(_) => "zip",
+            Self::Keystore(_) => "keystore",
+            Self::Config(_) => "config",
+            Self::Regexp(_) => "regexp",
+            Self::AlloyAbi(_) => "abi",
+            Self::AlloyTypes(_) => "abi_types",
+            Self::AlloyRpcTransport(_) => "rpc",
+            Self::AlloyContract(_) => "contract",
+            Self::UrlParse(_) => "url",
+            Self::Template(_) => "template",
+            Self::InputRequired(_) => "input_required",
+            Self::Anyhow(_) => "generic",
+        }
+    }
+}
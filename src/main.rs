@@ -7,24 +7,47 @@ pub mod setup;
 pub mod state;
 pub mod utils;
 
-use alloy::{network::AnyNetwork, providers::ProviderBuilder};
+use alloy::{
+    network::AnyNetwork,
+    providers::{Provider, ProviderBuilder},
+};
 use anyhow::anyhow;
-use clap::{Parser, Subcommand};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
 use console::style;
 use error::AppError;
 use messages::MessageType;
-use setup::Setup;
-use std::path::PathBuf;
+use regex::Regex;
+use setup::{Setup, Snapshot};
+use std::{
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use config::Config;
 use phases::{
-    actions_menu::ActionsMenuPhase, check_docker::DockerAvailablePhase,
-    check_status::CheckStatusPhase, select_network::SelectNetworkPhase,
-    select_node_ip::SelectNodeIP, select_private_key::SelectPrivateKeyPhase, Phase,
+    actions_menu::ActionsMenuPhase,
+    check_health::HealthCheckPhase,
+    check_ip::IpChangeCheckPhase,
+    debug_report::DebugReportPhase,
+    fleet::FleetPhase,
+    image_version::{ImageVersionAction, ImageVersionPhase},
+    maintenance::{AuthorSlotPhase, MaintenanceWindowPhase},
+    nodes::{NodesAction, NodesPhase},
+    pipeline::{Pipeline, RunContext, RunOptions},
+    self_update::SelfUpdatePhase,
+    view_logs::ViewLogsPhase,
+    Phase,
 };
 use utils::{
     config::{ConfigPath, JsonConfig},
-    logger,
+    fleet::{FleetFormat, FleetInventory},
+    i18n, logger,
+    logs::{LogFilter, LogLevel, ETHSTATS_SERVICE_NAME, PARITY_SERVICE_NAME},
+    maintenance::{UpdateAttempt, UpdateHistory, UpdateOutcome},
+    output::{self, track, OutputFormat},
+    profile::{self, NodePorts, Profile},
 };
 
 #[tokio::main]
@@ -32,77 +55,124 @@ async fn main() -> Result<(), AppError> {
     utils::set_heavy_panic();
     logger::init();
 
+    // Parsed first, so `--version` works even if the config doesn't match this version,
+    // self-update relies on it to check a new binary
+    let cli = Cli::parse();
+
     let config = Config::load_json(PathBuf::from(&ConfigPath::Relative {
         root: "./",
         path: "./config/custom.json",
     }))?;
+    i18n::init(cli.lang.as_deref())?;
+    output::init(cli.output);
+    let profile_name = cli
+        .profile
+        .clone()
+        .or_else(|| std::env::var(profile::PROFILE_ENV).ok());
+    profile::init(Profile::new(profile_name.as_deref())?)?;
 
-    let cli = Cli::parse();
     let run_result = match &cli.command {
-        Some(Commands::Update) => run_update().await,
-        None => run(&config).await,
+        Some(Commands::Update {
+            dry_run: false,
+            scheduled,
+        }) => run_update(&config, cli.offline, *scheduled).await,
+        Some(Commands::Update { dry_run: true, .. }) | Some(Commands::Diff) => {
+            run_diff(cli.offline).await
+        }
+        Some(Commands::Logs(args)) => ViewLogsPhase::new(Some(args.try_into()?)).run().await,
+        Some(Commands::DebugReport { output, redact_ips }) => match output {
+            Some(output) => {
+                DebugReportPhase::save(output.clone(), *redact_ips)
+                    .run()
+                    .await
+            }
+            None => {
+                DebugReportPhase::send(config.discord_webhook_url.clone())
+                    .run()
+                    .await
+            }
+        },
+        Some(Commands::CheckIp { daemon, interval }) => {
+            run_check_ip(*daemon, Duration::from_secs(*interval)).await
+        }
+        Some(Commands::RotateKeystore) => run_rotate_keystore().await,
+        Some(Commands::SelfUpdate { force }) => {
+            SelfUpdatePhase::new(config.release_feed_url.clone(), *force)
+                .run()
+                .await
+        }
+        Some(Commands::Version { command }) => {
+            ImageVersionPhase::new(command.as_ref().into(), cli.offline)
+                .run()
+                .await
+        }
+        Some(Commands::Fleet { inventory, format }) => run_fleet(&config, inventory, *format).await,
+        Some(Commands::Nodes { command }) => NodesPhase::new(command.as_ref().into()).run().await,
+        None => run(&config, &cli).await,
     };
 
     run_result.inspect_err(|e| {
-        let _ = cliclack::log::error(e);
+        let _ = output::log::error(e);
     })
 }
 
-async fn run(config: &Config) -> Result<(), AppError> {
-    cliclack::clear_screen()?;
-
-    print_intro()?;
-
-    DockerAvailablePhase {}.run().await?;
+async fn run(config: &Config, cli: &Cli) -> Result<(), AppError> {
     let mut state = state::State::read()?;
+    let profile = profile::active();
+    if state.ports.is_none() && !profile.is_default() {
+        let ports = NodePorts::allocate(&profile::used_ports(profile)?)?;
+        output::log::info(MessageType::ProfilePortsAssigned {
+            profile: profile.name(),
+            p2p: ports.p2p,
+            rpc: ports.rpc,
+        })?;
+        state.ports = Some(ports);
+    }
 
-    let mut select_network = SelectNetworkPhase::new(state.network.as_ref(), &config.networks);
-    select_network.run().await?;
-    state.network = select_network.network.cloned();
-
-    let mut select_private_key = SelectPrivateKeyPhase::new(state.private_key);
-    select_private_key.run().await?;
-    state.address = select_private_key
-        .private_key
-        .as_ref()
-        .map(utils::secp256k1_signing_key_to_eth_address);
-    state.private_key = select_private_key.private_key;
-
-    let mut select_node_ip = SelectNodeIP::new(state.ip);
-    select_node_ip.run().await?;
-    state.ip = select_node_ip.node_ip;
-
-    state.write()?;
-
-    let setup = Setup::new(state)?;
-    setup.run().await?;
+    let pipeline = Pipeline::default();
+    let mut context = RunContext {
+        config,
+        options: RunOptions {
+            offline: cli.offline,
+            network: cli.network.clone(),
+            ip: cli.ip,
+            generate_key: cli.generate_key,
+        },
+        state,
+    };
+    if cli.plan {
+        return pipeline.print_plan(&context);
+    }
 
-    cliclack::log::step(MessageType::DockerStarting)?;
+    if !output::is_jsonl() {
+        cliclack::clear_screen()?;
+        print_intro()?;
+    }
 
-    utils::exec::run_docker_compose_up()?;
+    pipeline.run(&mut context).await?;
 
-    cliclack::log::step(MessageType::DockerStarted)?;
+    // Actions menu is interactive only
+    if output::is_jsonl() {
+        return Ok(());
+    }
 
+    let state = context.state;
+    let network = state
+        .network
+        .as_ref()
+        .ok_or_else(|| anyhow!("No network selected"))?;
     let provider_remote = ProviderBuilder::new()
         .with_recommended_fillers()
         .network::<AnyNetwork>()
-        .on_http(setup.network.rpc.clone());
+        .on_http(network.rpc.clone());
     let provider_local = ProviderBuilder::new()
         .with_recommended_fillers()
         .network::<AnyNetwork>()
-        .on_http(
-            std::env::var("PARITY_URL")
-                .as_deref()
-                .unwrap_or("http://127.0.0.1:8545")
-                .parse()?,
-        );
-
-    let mut check_status =
-        CheckStatusPhase::new(provider_remote.clone(), &setup.network, setup.address).await?;
-    check_status.run().await?;
+        .on_http(profile::active().local_rpc_url(&state.ports())?);
 
     let mut actions_menu = ActionsMenuPhase::new(
         config.discord_webhook_url.clone(),
+        config.release_feed_url.clone(),
         provider_remote,
         provider_local,
     );
@@ -117,27 +187,233 @@ async fn run(config: &Config) -> Result<(), AppError> {
     Ok(())
 }
 
-async fn run_update() -> Result<(), AppError> {
-    cliclack::clear_screen()?;
-
+async fn run_diff(offline: bool) -> Result<(), AppError> {
     let state = state::State::read()?;
     if !state.is_complete() {
         return Err(anyhow!("State is missing some data").into());
     }
 
-    let setup = Setup::new(state)?;
-    setup.run().await?;
+    let diff = Setup::new(state)?
+        .with_offline(offline)
+        .render()
+        .await?
+        .diff()
+        .await?;
+    if diff.has_changes() {
+        output::note("Configuration diff", diff)?;
+    } else {
+        output::note("Configuration diff", MessageType::SetupNoChanges)?;
+    }
 
-    cliclack::log::step(MessageType::DockerStarting)?;
+    Ok(())
+}
 
-    utils::exec::run_docker_compose_pull()?;
-    utils::exec::run_docker_compose_up()?;
+async fn run_rotate_keystore() -> Result<(), AppError> {
+    let state = state::State::read()?;
+    if !state.is_complete() {
+        return Err(anyhow!("State is missing some data").into());
+    }
 
-    cliclack::log::step(MessageType::DockerStarted)?;
+    output::log::step(MessageType::FixKeystoreStepRegenerating)?;
+    Setup::new(state)?.rotate_keystore().await?;
 
+    // Parity reads password and keyfile on start only
+    output::log::step(MessageType::KeystoreStepRestarting)?;
+    utils::exec::run_docker_compose_restart(PARITY_SERVICE_NAME)?;
+
+    output::log::success(MessageType::KeystoreRotated)?;
     Ok(())
 }
 
+async fn run_update(config: &Config, offline: bool, scheduled: bool) -> Result<(), AppError> {
+    if !output::is_jsonl() {
+        cliclack::clear_screen()?;
+    }
+
+    let started_at = Utc::now();
+    let result = update(config, offline, scheduled).await;
+
+    let outcome = match &result {
+        Ok(true) => UpdateOutcome::Applied,
+        // Scheduler runs hourly, recording skips would push real attempts out of the history
+        Ok(false) => return Ok(()),
+        Err(e) => UpdateOutcome::Failed {
+            error: e.to_string(),
+        },
+    };
+    UpdateHistory::record(UpdateAttempt {
+        started_at,
+        finished_at: Utc::now(),
+        scheduled,
+        nop_version: env!("CARGO_PKG_VERSION").to_owned(),
+        outcome,
+    })
+    .await?;
+
+    result.map(|_| ())
+}
+
+/// Returns `false` if the scheduled update has been skipped
+async fn update(config: &Config, offline: bool, scheduled: bool) -> Result<bool, AppError> {
+    let state = state::State::read()?;
+    if !state.is_complete() {
+        return Err(anyhow!("State is missing some data").into());
+    }
+
+    let setup = Setup::new(state)?.with_offline(offline);
+    if scheduled {
+        let history = UpdateHistory::read().await?;
+        if history.is_retry_postponed(env!("CARGO_PKG_VERSION"), Utc::now()) {
+            output::log::info(MessageType::MaintenanceRetryPostponed)?;
+            return Ok(false);
+        }
+        if !has_pending_changes(&setup, &history).await? {
+            output::log::info(MessageType::MaintenanceNothingToApply)?;
+            return Ok(false);
+        }
+
+        let mut maintenance_window =
+            MaintenanceWindowPhase::new(&config.maintenance, setup.address);
+        track("maintenance_window", maintenance_window.run()).await?;
+        if maintenance_window.skipped {
+            return Ok(false);
+        }
+    }
+
+    let provider_remote = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .network::<AnyNetwork>()
+        .on_http(setup.network.rpc.clone());
+    let provider_local = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .network::<AnyNetwork>()
+        .on_http(profile::active().local_rpc_url(&setup.ports)?);
+    let chain_id = match Setup::known_chain_id(&setup.network, offline).await? {
+        Some(chain_id) => chain_id,
+        None => provider_remote.get_chain_id().await?,
+    };
+
+    let snapshot = Snapshot::take().await?;
+    let result: Result<(), AppError> = async {
+        track("setup", setup.run()).await?;
+
+        output::log::step(MessageType::DockerStarting)?;
+
+        if !offline {
+            track("docker_pull", async {
+                utils::exec::run_docker_compose_pull()
+            })
+            .await?;
+        }
+        // Checked after the pull, which may take a while
+        if let Some(step_duration) = Setup::cached_chainspec().await?.step_duration() {
+            let mut author_slot = AuthorSlotPhase::new(
+                provider_remote.clone(),
+                setup.address,
+                step_duration,
+                Duration::from_secs(config.maintenance.author_margin_secs),
+            );
+            track("author_slot", author_slot.run()).await?;
+        }
+        track("docker_start", async {
+            utils::exec::run_docker_compose_up()
+        })
+        .await?;
+
+        let mut check_health = HealthCheckPhase::new(provider_local.clone(), chain_id)
+            .with_grace_period(Duration::from_secs(config.maintenance.grace_period_secs));
+        track("health_check", check_health.run()).await?;
+        if !check_health.report.is_healthy() {
+            return Err(anyhow!("Node is not healthy after update").into());
+        }
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        output::log::warning(MessageType::UpdateRollingBack {
+            reason: e.to_string(),
+        })?;
+        let rollback: Result<(), AppError> = async {
+            track("rollback", async {
+                snapshot.restore().await?;
+                utils::exec::run_docker_compose_up()
+            })
+            .await?;
+
+            let mut check_health = HealthCheckPhase::new(provider_local, chain_id);
+            track("health_check", check_health.run()).await?;
+            if check_health.report.is_healthy() {
+                output::log::success(MessageType::UpdateRolledBack)?;
+            }
+
+            Ok(())
+        }
+        .await;
+
+        // The update error stays the primary one, so it isn't lost when rollback fails too
+        if let Err(rollback_error) = rollback {
+            return Err(anyhow!(
+                "{}",
+                MessageType::UpdateRollbackFailed {
+                    error: e.to_string(),
+                    rollback_error: rollback_error.to_string(),
+                }
+            )
+            .into());
+        }
+
+        return Err(e);
+    }
+
+    output::log::step(MessageType::DockerStarted)?;
+
+    Ok(true)
+}
+
+/// Scheduled update applies changed setup files and parity image, as well as a NOP release
+/// installed since the last applied update
+async fn has_pending_changes(setup: &Setup, history: &UpdateHistory) -> Result<bool, AppError> {
+    if setup.render().await?.diff().await?.has_changes() {
+        return Ok(true);
+    }
+
+    Ok(history.last_applied().map_or(true, |attempt| {
+        attempt.nop_version != env!("CARGO_PKG_VERSION")
+    }))
+}
+
+async fn run_fleet(
+    config: &Config,
+    inventory_path: &Path,
+    format: FleetFormat,
+) -> Result<(), AppError> {
+    let inventory = FleetInventory::read(inventory_path).await?;
+    let network = config
+        .networks
+        .get(&inventory.network)
+        .ok_or_else(|| anyhow!("Unknown network `{}` in fleet inventory", inventory.network))?;
+
+    FleetPhase::new(inventory, network, format).run().await
+}
+
+async fn run_check_ip(daemon: bool, interval: Duration) -> Result<(), AppError> {
+    let mut check_ip = IpChangeCheckPhase::new(daemon);
+    if !daemon {
+        return check_ip.run().await;
+    }
+
+    loop {
+        // Temporary failures (e.g. network outage) must not stop the daemon
+        if let Err(e) = check_ip.run().await {
+            output::log::error(e)?;
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
 fn print_intro() -> Result<(), AppError> {
     cliclack::intro(
         style(
@@ -176,9 +452,172 @@ fn print_intro() -> Result<(), AppError> {
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Reuse the cached chainspec instead of downloading it
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Node profile to manage, `NOP_PROFILE` environment variable is used if not set
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Language of messages (e.g. `es`), `LANG` environment variable is used if not set
+    #[arg(long, global = true)]
+    lang: Option<String>,
+    /// Output format, `jsonl` prints events on stdout and disables prompts. Goes before the subcommand
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    /// Network to join, replaces the configured one
+    #[arg(long)]
+    network: Option<String>,
+    /// Node IP address, replaces the configured one
+    #[arg(long)]
+    ip: Option<IpAddr>,
+    /// Generate a new private key if there is none yet
+    #[arg(long)]
+    generate_key: bool,
+    /// Show phases of the setup run and which of them would run or be skipped, then exit
+    #[arg(long)]
+    plan: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Update,
+    Update {
+        /// Show configuration changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+        /// Run by scheduler: respect the maintenance window and stagger restarts across nodes
+        #[arg(long)]
+        scheduled: bool,
+    },
+    /// Show difference between the current node configuration and the one setup would generate
+    Diff,
+    /// Generate new keystore password and re-encrypt the node key with it
+    RotateKeystore,
+    /// Download, verify and install the latest NOP release
+    SelfUpdate {
+        /// Reinstall even if the latest release is already running
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show parity image version and pin, upgrade or roll it back
+    Version {
+        #[command(subcommand)]
+        command: Option<VersionCommand>,
+    },
+    /// List node profiles configured on this host or show their status
+    Nodes {
+        #[command(subcommand)]
+        command: Option<NodesCommand>,
+    },
+    /// Show registration, onboarding, stake and health of nodes from fleet inventory
+    Fleet {
+        /// Inventory file with network name and node addresses, RPC and metrics endpoints
+        #[arg(short, long, default_value = "./fleet.json")]
+        inventory: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = FleetFormat::Table)]
+        format: FleetFormat,
+    },
+    /// Show parity and ethstats containers logs
+    Logs(LogsArgs),
+    /// Collect debug report and send it to AirDao support team or save it into a file
+    DebugReport {
+        /// Save report bundle (`.tar.gz`) into the file instead of sending it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Hide IP addresses in the report
+        #[arg(long)]
+        redact_ips: bool,
+    },
+    /// Check whether public IP of the node has changed and update node configuration
+    CheckIp {
+        /// Keep checking periodically and apply changes without confirmation
+        #[arg(long)]
+        daemon: bool,
+        /// Interval between checks in daemon mode, seconds
+        #[arg(long, default_value_t = 300)]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum VersionCommand {
+    /// Show running, configured and template parity images (default)
+    Show,
+    /// Pin parity image, so `update` doesn't change it
+    Pin {
+        /// Image tag (e.g. v3.3.3-amb1.2.4) or full image reference
+        version: String,
+    },
+    /// Remove the pin and switch to the image from setup templates
+    Upgrade,
+    /// Switch back to the image used before the current one
+    Rollback,
+}
+
+impl From<Option<&VersionCommand>> for ImageVersionAction {
+    fn from(command: Option<&VersionCommand>) -> Self {
+        match command {
+            None | Some(VersionCommand::Show) => Self::Show,
+            Some(VersionCommand::Pin { version }) => Self::Pin(version.clone()),
+            Some(VersionCommand::Upgrade) => Self::Upgrade,
+            Some(VersionCommand::Rollback) => Self::Rollback,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum NodesCommand {
+    /// List profiles with their network, address and ports (default)
+    List,
+    /// Show containers and sync state of every profile
+    Status,
+}
+
+impl From<Option<&NodesCommand>> for NodesAction {
+    fn from(command: Option<&NodesCommand>) -> Self {
+        match command {
+            None | Some(NodesCommand::List) => Self::List,
+            Some(NodesCommand::Status) => Self::Status,
+        }
+    }
+}
+
+#[derive(Args)]
+struct LogsArgs {
+    /// Follow log output
+    #[arg(short, long)]
+    follow: bool,
+    /// Show only lines with the given or higher level
+    #[arg(short, long, value_enum)]
+    level: Option<LogLevel>,
+    /// Show logs since timestamp (e.g. 2024-11-01T10:00:00) or relative (e.g. 42m)
+    #[arg(long)]
+    since: Option<String>,
+    /// Show logs before timestamp (e.g. 2024-11-01T10:00:00) or relative (e.g. 42m)
+    #[arg(long)]
+    until: Option<String>,
+    /// Show only lines matching the regular expression
+    #[arg(short, long)]
+    grep: Option<String>,
+    /// Number of lines to show from the end of the logs
+    #[arg(short = 'n', long)]
+    tail: Option<usize>,
+    /// Services to show logs for (all by default)
+    #[arg(value_parser = [PARITY_SERVICE_NAME, ETHSTATS_SERVICE_NAME])]
+    services: Vec<String>,
+}
+
+impl TryFrom<&LogsArgs> for LogFilter {
+    type Error = AppError;
+
+    fn try_from(args: &LogsArgs) -> Result<Self, Self::Error> {
+        Ok(Self {
+            services: args.services.clone(),
+            level: args.level,
+            since: args.since.clone(),
+            until: args.until.clone(),
+            search: args.grep.as_deref().map(Regex::new).transpose()?,
+            follow: args.follow,
+            tail: args.tail,
+        })
+    }
 }
@@ -1,145 +1,647 @@
 use alloy::primitives::Address;
-use chrono::{DateTime, Utc};
-use std::{net::IpAddr, time::Duration};
-use strum_macros::Display;
-
-#[derive(Display, Clone, PartialEq, Eq)]
+use chrono::{DateTime, NaiveTime, Utc};
+use semver::Version;
+use std::{
+    fmt::{self, Display},
+    net::IpAddr,
+    time::Duration,
+};
+use strum_macros::{EnumDiscriminants, EnumIter, IntoStaticStr};
+
+use crate::utils::i18n;
+
+/// User-facing message, the text comes from the catalog of the active locale, see [`i18n`]
+#[derive(Debug, Clone, PartialEq, Eq, EnumDiscriminants)]
+#[strum_discriminants(name(MessageKey), derive(IntoStaticStr, EnumIter))]
 pub enum MessageType<'a> {
-    #[strum(serialize = "Which network do you want to be onboarded to?")]
     NetworkRequest,
-
-    #[strum(serialize = "Network {network:?}")]
-    NetworkSelected { network: &'a str },
-
-    #[strum(serialize = "No private key setup yet. What do you want to do?")]
+    NetworkSelected {
+        network: &'a str,
+    },
     NoPrivateKey,
-
-    #[strum(serialize = "Input existing key manually")]
     PrivateKeyInputExistingSelection,
-
-    #[strum(serialize = "Generate new key automatically")]
     PrivateKeyGenerateNewSelection,
-
-    #[strum(serialize = "Please provide your private key (in hex form):")]
     PrivateKeyInputManually,
-
-    #[strum(serialize = "Private key invalid length (64 hex characters max)")]
     PrivateKeyInvalidLength,
-
-    #[strum(serialize = "Private key should be in hex form")]
     PrivateKeyInvalidFormat,
-
-    #[strum(serialize = "✅ Private key verified. Your address is {address:?}")]
-    PrivateKeyVerified { address: Address },
-
-    #[strum(
-        serialize = "Please provide the IP address, which you will be using for your node. \nIs {ip} correct?"
-    )]
-    NodeIpConfirmRequest { ip: IpAddr },
-
-    #[strum(serialize = "Provide the IP address, which you will be using for your node")]
+    PrivateKeyVerified {
+        address: Address,
+    },
+    NodeIpSelectRequest,
+    NodeIpSourcesDisagree {
+        ips: String,
+    },
     NodeIpInputManually,
-
-    #[strum(serialize = "{ip} is not a valid IP address")]
-    NodeIpInvalidFormat { ip: &'a str },
-
-    #[strum(serialize = "Node IP defined as {ip}")]
-    NodeIpInfo { ip: &'a IpAddr },
-
-    #[strum(
-        serialize = "⛔ Docker is required, and was not found. Please verify your installation"
-    )]
+    NodeIpInvalidFormat {
+        ip: &'a str,
+    },
+    NodeIpInfo {
+        ip: &'a IpAddr,
+    },
+    NodeIpv6Unsupported {
+        ip: &'a IpAddr,
+    },
     DockerMissing,
-
-    #[strum(serialize = "✅ Docker is installed")]
     DockerInstalled,
-
-    #[strum(serialize = "Starting docker containers... 🐳")]
     DockerStarting,
-
-    #[strum(serialize = "🎉 Your node is launched! 🎉")]
     DockerStarted,
-
-    #[strum(serialize = "🎉 Your node configuration is ready 🎉")]
+    HealthChecking,
+    HealthCheckPassed,
+    HealthCheckTimedOut {
+        timeout: Duration,
+    },
+    HealthCheckGracePeriod {
+        grace_period: Duration,
+    },
+    HealthCheckUnstable {
+        grace_period: Duration,
+    },
+    HealthCheckFailed {
+        summary: String,
+    },
     SetupCompleted,
-
-    #[strum(
-        serialize = "Your node is not registered in the network. Register here: {explorer_url}/explorer/node-setup/"
-    )]
-    NodeNotRegistered { explorer_url: &'a str },
-
-    #[strum(
-        serialize = "Node registered and onboarded to the network🎉. You can check it here: {explorer_url}/explorer/apollo/{node_addr:?}"
-    )]
+    NodeNotRegistered {
+        explorer_url: &'a str,
+    },
     NodeOnboarded {
         explorer_url: &'a str,
         node_addr: &'a Address,
     },
-
-    #[strum(
-        serialize = "Please wait until your node is onboarded to the network, Left: {time_to_wait:?}"
-    )]
-    NodeOnboarding { time_to_wait: Duration },
-
-    #[strum(serialize = "You can now perform one of the following actions")]
+    NodeOnboarding {
+        time_to_wait: Duration,
+    },
     SelectActionMenu,
-
-    #[strum(serialize = "📁 Send debug information to AirDao support team")]
     LogsActionMenuItem,
-
-    #[strum(serialize = "🔍 Try to find and fix issues with your node setup")]
     CheckActionMenuItem,
-
-    #[strum(serialize = "👋 Quit NOP")]
+    ViewLogsActionMenuItem,
     QuitActionMenuItem,
-
-    #[strum(serialize = "Checking...")]
     Checking,
-
-    #[strum(serialize = "Syncing {progress}%... please wait")]
-    Syncing { progress: u64 },
-
-    #[strum(serialize = "Sync: OK")]
+    Syncing {
+        progress: u64,
+    },
     NotSyncing,
-
-    #[strum(serialize = "Fork: OK")]
     NotForked,
-
-    #[strum(serialize = "Fork: Parity has forked...")]
     Forked,
-
-    #[strum(serialize = "Do you want to fix this issue?")]
     AskFixForkIssue,
-
-    #[strum(serialize = "Fixing fork...")]
     FixForkStepFixing,
-
-    #[strum(serialize = "Removing chains...")]
     FixForkStepRemovingChains,
-
-    #[strum(serialize = "Downloading backup...")]
     FixForkStepDownloadingBackup,
-
-    #[strum(serialize = "Fork fixed")]
     FixForkStepFixed,
+    LogsCheckOk,
+    DiagnosisFinding {
+        explanation: String,
+        occurrences: usize,
+        sample: String,
+    },
+    DiagnosisDatabaseCorruption,
+    DiagnosisEngineSignerPassword,
+    DiagnosisNatMisconfiguration,
+    DiagnosisOutOfDisk,
+    DiagnosisClockDrift,
+    AskApplyAutomatedFix,
+    FixKeystoreStepRegenerating,
+    NopVersionOk {
+        version: &'a Version,
+    },
+    NopVersionOutdated {
+        current: &'a Version,
+        latest: &'a Version,
+    },
+    NopVersionCriticalUpdate {
+        current: &'a Version,
+        latest: &'a Version,
+    },
+    NopVersionUnsupported {
+        current: &'a Version,
+        min_supported: &'a Version,
+        latest: &'a Version,
+    },
+    NopVersionUnknown {
+        reason: String,
+    },
+    FixNopVersionStepUpdate,
+    AskFixNopVersionIssue,
+    LogsReceivedAt {
+        timestamp: DateTime<Utc>,
+    },
+    LogsSendError {
+        msg: String,
+    },
+    DebugReportAskRedactIps,
+    DebugReportAskSend,
+    DebugReportNotSent,
+    DebugReportSaved {
+        path: String,
+    },
+    LogsSelectServices,
+    LogsSelectLevel,
+    LogsSearchRequest,
+    LogsInvalidSearch {
+        msg: String,
+    },
+    LogsFollowRequest,
+    LogsStreaming,
+    LogsImportStalled {
+        elapsed: Duration,
+    },
+    HostHintCpu {
+        recommended: usize,
+    },
+    HostHintMemory {
+        recommended_gib: u64,
+    },
+    HostHintDisk {
+        recommended_gib: u64,
+    },
+    HostHintInodes,
+    HostHintPort {
+        port: u16,
+    },
+    HostHintClock,
+    HostHintDistribution,
+    HostHintKernel {
+        min_version: u32,
+    },
+    HostRequirementsFailed,
+    P2pReachable,
+    P2pNoPeers {
+        port: u16,
+    },
+    P2pNoInboundPeers {
+        ip: IpAddr,
+        port: u16,
+    },
+    P2pIpMismatch {
+        ip: IpAddr,
+        public_ip: IpAddr,
+    },
+    P2pRpcUnavailable {
+        msg: String,
+    },
+    IpChangeNoStoredIp,
+    IpChangeNotChanged {
+        ip: IpAddr,
+    },
+    IpChangeUnknown {
+        reason: String,
+    },
+    IpChangeDetected {
+        old: IpAddr,
+        new: IpAddr,
+    },
+    AskApplyIpChange {
+        new: IpAddr,
+    },
+    IpChangeStepRegeneratingConfig,
+    IpChangeStepRestarting,
+    IpChangeApplied {
+        ip: IpAddr,
+    },
+    SetupNoChanges,
+    KeystoreStepRestarting,
+    KeystoreRotated,
+    ChainspecUpdated,
+    ImageVersionPinned {
+        image: &'a str,
+    },
+    ImageVersionRollback {
+        from: &'a str,
+        to: &'a str,
+    },
+    ImageVersionNoPrevious,
+    ImageVersionApplied {
+        image: &'a str,
+    },
+    SelfUpdateUpToDate {
+        version: &'a Version,
+    },
+    SelfUpdateStepDownloading {
+        current: &'a Version,
+        latest: &'a Version,
+    },
+    SelfUpdateSignatureVerified,
+    SelfUpdateInstalled {
+        version: &'a Version,
+    },
+    MaintenanceOutsideWindow {
+        start: NaiveTime,
+        end: NaiveTime,
+    },
+    MaintenanceStaggerDelay {
+        delay: Duration,
+    },
+    MaintenanceAuthorWait {
+        delay: Duration,
+    },
+    MaintenanceAuthorCheckFailed {
+        reason: String,
+    },
+    MaintenanceNothingToApply,
+    MaintenanceRetryPostponed,
+    UpdateRollingBack {
+        reason: String,
+    },
+    UpdateRolledBack,
+    UpdateRollbackFailed {
+        error: String,
+        rollback_error: String,
+    },
+    ProfilePortsAssigned {
+        profile: &'a str,
+        p2p: u16,
+        rpc: u16,
+    },
+    NodesNotConfigured,
+    FleetChecking {
+        count: usize,
+        network: &'a str,
+    },
+    FleetChecked,
+    PipelinePhaseSatisfied {
+        phase: &'a str,
+    },
+    PipelinePhaseCompleted {
+        phase: &'a str,
+    },
+    StatusNotFound,
+    StatusUnknown,
+    PeersConnected {
+        count: u64,
+    },
+    PeersConnectedOf {
+        count: u64,
+        max: u64,
+    },
+    PeersConnectedUnknown,
+    HealthContainerStatus {
+        container: &'a str,
+        status: String,
+    },
+    HealthChainId {
+        chain_id: u64,
+        expected: u64,
+    },
+    HealthChainIdUnavailable {
+        expected: u64,
+    },
+    HealthImportStalled {
+        block: u64,
+    },
+    HealthImportStalledUnknownBlock,
+    HealthRpcError {
+        error: &'a str,
+    },
+    P2pBindingLocal,
+    P2pBindingForwarded,
+    P2pBindingMismatch {
+        public_ip: IpAddr,
+    },
+    P2pExternalIp {
+        ip: IpAddr,
+        binding: String,
+    },
+    P2pInboundPeers {
+        count: usize,
+    },
+    P2pOutboundPeers {
+        count: usize,
+    },
+    DiffImageUnchanged {
+        image: &'a str,
+    },
+    DiffImageChanged {
+        current: String,
+        image: String,
+    },
+    DiffImageNew {
+        image: String,
+    },
+    DiffFileNew {
+        file: &'a str,
+    },
+    DiffFileUnchanged {
+        file: &'a str,
+    },
+    DiffFileChanged {
+        file: &'a str,
+    },
+    DiffFilesRegenerated {
+        files: String,
+    },
+    NodesPorts {
+        p2p: u16,
+        rpc: u16,
+    },
+    NodesSynced {
+        block: u64,
+    },
+    NodesSyncing {
+        block: u64,
+    },
+    NodesRpcError {
+        msg: String,
+    },
+    NodesRpcTimeout,
+    ImageVersionRunning {
+        image: &'a str,
+    },
+    ImageVersionConfigured {
+        image: &'a str,
+    },
+    ImageVersionTemplate {
+        image: &'a str,
+    },
+    ImageVersionPinnedImage {
+        image: &'a str,
+    },
+    ImageVersionPending {
+        image: &'a str,
+    },
+    ImageVersionHistory,
+    ImageVersionNone,
+    FleetColumnNode,
+    FleetColumnRegistration,
+    FleetColumnStake,
+    FleetColumnHealth,
+    FleetErrors,
+    FleetOnboarding,
+    FleetOnboardingLeft {
+        secs: u64,
+    },
+    FleetOnboarded,
+    FleetNotRegistered,
+    FleetLag {
+        lag: u64,
+    },
+    FleetPeers {
+        peers: u64,
+    },
+    FleetSyncing,
+    FleetMetricsDown,
+    HostStatusPass,
+    HostStatusWarn,
+    HostStatusFail,
+    HostCheckCpu,
+    HostCheckMemory,
+    HostCheckDisk,
+    HostCheckInodes,
+    HostCheckP2pTcpPort,
+    HostCheckP2pUdpPort,
+    HostCheckRpcPort,
+    HostCheckClock,
+    HostCheckDistribution,
+    HostCheckKernel,
+    HostValueCores {
+        count: usize,
+    },
+    HostValueMemory {
+        gib: String,
+    },
+    HostValueDiskFree {
+        gib: String,
+    },
+    HostValueInodesFree {
+        count: u64,
+    },
+    HostValuePortAvailable {
+        port: u16,
+    },
+    HostValuePortUsedByParity {
+        port: u16,
+    },
+    HostValuePortInUse {
+        port: u16,
+    },
+    HostValueClockOffset {
+        offset: String,
+    },
+    HostValueUnknown {
+        error: String,
+    },
+    NodeIpPublicLabel {
+        confirmed: usize,
+        total: usize,
+    },
+    NodeIpInterfaceLabel {
+        interface: &'a str,
+    },
+    NodeIpPrivateInterfaceLabel {
+        interface: &'a str,
+    },
+    IpChangeNoSources,
+    IpChangeSourcesDisagree,
+    IpChangeNotConfirmed {
+        confirmed: usize,
+        total: usize,
+        ip: IpAddr,
+    },
+}
 
-    #[strum(serialize = "Git version: OK")]
-    GitVersionOk,
-
-    #[strum(serialize = "Git version: old version detected (Local: {local} Remote: {remote})!")]
-    GitVersionOld { local: String, remote: String },
-
-    #[strum(
-        serialize = "The NOP will be updated now and node will be restarted...\nYou can run NOP again after the update is complete."
-    )]
-    FixGitVersionStepUpdate,
-
-    #[strum(serialize = "Do you want to fix this issue?")]
-    AskFixGitVersionIssue,
-
-    #[strum(serialize = "Logs received at {timestamp:?}")]
-    LogsReceivedAt { timestamp: DateTime<Utc> },
+impl MessageType<'_> {
+    /// Values substituted into `{name}` placeholders of the message text
+    fn args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::NetworkSelected { network } => vec![("network", format!("{network:?}"))],
+            Self::PrivateKeyVerified { address } => vec![("address", format!("{address:?}"))],
+            Self::NodeIpSourcesDisagree { ips } => vec![("ips", ips.to_string())],
+            Self::NodeIpInvalidFormat { ip } => vec![("ip", ip.to_string())],
+            Self::NodeIpInfo { ip } => vec![("ip", ip.to_string())],
+            Self::NodeIpv6Unsupported { ip } => vec![("ip", ip.to_string())],
+            Self::HealthCheckTimedOut { timeout } => vec![("timeout", format!("{timeout:?}"))],
+            Self::HealthCheckGracePeriod { grace_period } => {
+                vec![("grace_period", format!("{grace_period:?}"))]
+            }
+            Self::HealthCheckUnstable { grace_period } => {
+                vec![("grace_period", format!("{grace_period:?}"))]
+            }
+            Self::HealthCheckFailed { summary } => vec![("summary", summary.to_string())],
+            Self::NodeNotRegistered { explorer_url } => {
+                vec![("explorer_url", explorer_url.to_string())]
+            }
+            Self::NodeOnboarded {
+                explorer_url,
+                node_addr,
+            } => vec![
+                ("explorer_url", explorer_url.to_string()),
+                ("node_addr", format!("{node_addr:?}")),
+            ],
+            Self::NodeOnboarding { time_to_wait } => {
+                vec![("time_to_wait", format!("{time_to_wait:?}"))]
+            }
+            Self::Syncing { progress } => vec![("progress", progress.to_string())],
+            Self::DiagnosisFinding {
+                explanation,
+                occurrences,
+                sample,
+            } => vec![
+                ("explanation", explanation.to_string()),
+                ("occurrences", occurrences.to_string()),
+                ("sample", sample.to_string()),
+            ],
+            Self::NopVersionOk { version } => vec![("version", version.to_string())],
+            Self::NopVersionOutdated { current, latest } => vec![
+                ("current", current.to_string()),
+                ("latest", latest.to_string()),
+            ],
+            Self::NopVersionCriticalUpdate { current, latest } => vec![
+                ("current", current.to_string()),
+                ("latest", latest.to_string()),
+            ],
+            Self::NopVersionUnsupported {
+                current,
+                min_supported,
+                latest,
+            } => vec![
+                ("current", current.to_string()),
+                ("min_supported", min_supported.to_string()),
+                ("latest", latest.to_string()),
+            ],
+            Self::NopVersionUnknown { reason } => vec![("reason", reason.to_string())],
+            Self::LogsReceivedAt { timestamp } => vec![("timestamp", format!("{timestamp:?}"))],
+            Self::LogsSendError { msg } => vec![("msg", msg.to_string())],
+            Self::DebugReportSaved { path } => vec![("path", path.to_string())],
+            Self::LogsInvalidSearch { msg } => vec![("msg", msg.to_string())],
+            Self::LogsImportStalled { elapsed } => vec![("elapsed", format!("{elapsed:?}"))],
+            Self::HostHintCpu { recommended } => vec![("recommended", recommended.to_string())],
+            Self::HostHintMemory { recommended_gib } => {
+                vec![("recommended_gib", recommended_gib.to_string())]
+            }
+            Self::HostHintDisk { recommended_gib } => {
+                vec![("recommended_gib", recommended_gib.to_string())]
+            }
+            Self::HostHintPort { port } => vec![("port", port.to_string())],
+            Self::HostHintKernel { min_version } => vec![("min_version", min_version.to_string())],
+            Self::P2pNoPeers { port } => vec![("port", port.to_string())],
+            Self::P2pNoInboundPeers { ip, port } => {
+                vec![("ip", ip.to_string()), ("port", port.to_string())]
+            }
+            Self::P2pIpMismatch { ip, public_ip } => {
+                vec![("ip", ip.to_string()), ("public_ip", public_ip.to_string())]
+            }
+            Self::P2pRpcUnavailable { msg } => vec![("msg", msg.to_string())],
+            Self::IpChangeNotChanged { ip } => vec![("ip", ip.to_string())],
+            Self::IpChangeUnknown { reason } => vec![("reason", reason.to_string())],
+            Self::IpChangeDetected { old, new } => {
+                vec![("old", old.to_string()), ("new", new.to_string())]
+            }
+            Self::AskApplyIpChange { new } => vec![("new", new.to_string())],
+            Self::IpChangeApplied { ip } => vec![("ip", ip.to_string())],
+            Self::ImageVersionPinned { image } => vec![("image", image.to_string())],
+            Self::ImageVersionRollback { from, to } => {
+                vec![("from", from.to_string()), ("to", to.to_string())]
+            }
+            Self::ImageVersionApplied { image } => vec![("image", image.to_string())],
+            Self::SelfUpdateUpToDate { version } => vec![("version", version.to_string())],
+            Self::SelfUpdateStepDownloading { current, latest } => vec![
+                ("current", current.to_string()),
+                ("latest", latest.to_string()),
+            ],
+            Self::SelfUpdateInstalled { version } => vec![("version", version.to_string())],
+            Self::MaintenanceOutsideWindow { start, end } => {
+                vec![("start", start.to_string()), ("end", end.to_string())]
+            }
+            Self::MaintenanceStaggerDelay { delay } => vec![("delay", format!("{delay:?}"))],
+            Self::MaintenanceAuthorWait { delay } => vec![("delay", format!("{delay:?}"))],
+            Self::MaintenanceAuthorCheckFailed { reason } => vec![("reason", reason.to_string())],
+            Self::UpdateRollingBack { reason } => vec![("reason", reason.to_string())],
+            Self::UpdateRollbackFailed {
+                error,
+                rollback_error,
+            } => vec![
+                ("error", error.to_string()),
+                ("rollback_error", rollback_error.to_string()),
+            ],
+            Self::ProfilePortsAssigned { profile, p2p, rpc } => vec![
+                ("profile", profile.to_string()),
+                ("p2p", p2p.to_string()),
+                ("rpc", rpc.to_string()),
+            ],
+            Self::FleetChecking { count, network } => vec![
+                ("count", count.to_string()),
+                ("network", network.to_string()),
+            ],
+            Self::PipelinePhaseSatisfied { phase } | Self::PipelinePhaseCompleted { phase } => {
+                vec![("phase", phase.to_string())]
+            }
+            Self::PeersConnected { count } => vec![("count", count.to_string())],
+            Self::PeersConnectedOf { count, max } => {
+                vec![("count", count.to_string()), ("max", max.to_string())]
+            }
+            Self::HealthContainerStatus { container, status } => vec![
+                ("container", container.to_string()),
+                ("status", status.to_string()),
+            ],
+            Self::HealthChainId { chain_id, expected } => vec![
+                ("chain_id", chain_id.to_string()),
+                ("expected", expected.to_string()),
+            ],
+            Self::HealthChainIdUnavailable { expected } => vec![("expected", expected.to_string())],
+            Self::HealthImportStalled { block } => vec![("block", block.to_string())],
+            Self::HealthRpcError { error } => vec![("error", error.to_string())],
+            Self::P2pBindingMismatch { public_ip } => vec![("public_ip", public_ip.to_string())],
+            Self::P2pExternalIp { ip, binding } => {
+                vec![("ip", ip.to_string()), ("binding", binding.to_string())]
+            }
+            Self::P2pInboundPeers { count } => vec![("count", count.to_string())],
+            Self::P2pOutboundPeers { count } => vec![("count", count.to_string())],
+            Self::DiffImageUnchanged { image } => vec![("image", image.to_string())],
+            Self::DiffImageChanged { current, image } => vec![
+                ("current", current.to_string()),
+                ("image", image.to_string()),
+            ],
+            Self::DiffImageNew { image } => vec![("image", image.to_string())],
+            Self::DiffFileNew { file } => vec![("file", file.to_string())],
+            Self::DiffFileUnchanged { file } => vec![("file", file.to_string())],
+            Self::DiffFileChanged { file } => vec![("file", file.to_string())],
+            Self::DiffFilesRegenerated { files } => vec![("files", files.to_string())],
+            Self::NodesPorts { p2p, rpc } => {
+                vec![("p2p", p2p.to_string()), ("rpc", rpc.to_string())]
+            }
+            Self::NodesSynced { block } => vec![("block", block.to_string())],
+            Self::NodesSyncing { block } => vec![("block", block.to_string())],
+            Self::NodesRpcError { msg } => vec![("msg", msg.to_string())],
+            Self::ImageVersionRunning { image } => vec![("image", image.to_string())],
+            Self::ImageVersionConfigured { image } => vec![("image", image.to_string())],
+            Self::ImageVersionTemplate { image } => vec![("image", image.to_string())],
+            Self::ImageVersionPinnedImage { image } => vec![("image", image.to_string())],
+            Self::ImageVersionPending { image } => vec![("image", image.to_string())],
+            Self::FleetOnboardingLeft { secs } => vec![("secs", secs.to_string())],
+            Self::FleetLag { lag } => vec![("lag", lag.to_string())],
+            Self::FleetPeers { peers } => vec![("peers", peers.to_string())],
+            Self::HostValueCores { count } => vec![("count", count.to_string())],
+            Self::HostValueMemory { gib } => vec![("gib", gib.to_string())],
+            Self::HostValueDiskFree { gib } => vec![("gib", gib.to_string())],
+            Self::HostValueInodesFree { count } => vec![("count", count.to_string())],
+            Self::HostValuePortAvailable { port } => vec![("port", port.to_string())],
+            Self::HostValuePortUsedByParity { port } => vec![("port", port.to_string())],
+            Self::HostValuePortInUse { port } => vec![("port", port.to_string())],
+            Self::HostValueClockOffset { offset } => vec![("offset", offset.to_string())],
+            Self::HostValueUnknown { error } => vec![("error", error.to_string())],
+            Self::NodeIpPublicLabel { confirmed, total } => vec![
+                ("confirmed", confirmed.to_string()),
+                ("total", total.to_string()),
+            ],
+            Self::NodeIpInterfaceLabel { interface } => vec![("interface", interface.to_string())],
+            Self::NodeIpPrivateInterfaceLabel { interface } => {
+                vec![("interface", interface.to_string())]
+            }
+            Self::IpChangeNotConfirmed {
+                confirmed,
+                total,
+                ip,
+            } => vec![
+                ("confirmed", confirmed.to_string()),
+                ("total", total.to_string()),
+                ("ip", ip.to_string()),
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
 
-    #[strum(serialize = "Failed send logs. {msg}")]
-    LogsSendError { msg: String },
+impl Display for MessageType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key: &'static str = MessageKey::from(self).into();
+        f.write_str(&i18n::render(i18n::message(key), &self.args()))
+    }
 }
@@ -10,18 +10,23 @@ use alloy::{
     rpc::types::{BlockTransactionsKind, SyncStatus},
     transports::Transport,
 };
-use anyhow::anyhow;
-use chrono::{DateTime, Utc};
 use futures_util::{future::BoxFuture, FutureExt};
-use serde::Deserialize;
-use std::path::PathBuf;
 
-use super::Phase;
+use super::{
+    check_ip::IpChangeCheckPhase, check_p2p::P2pCheckPhase, debug_report::DebugReportPhase,
+    self_update::SelfUpdatePhase, view_logs::ViewLogsPhase, Phase,
+};
 use crate::{
     error::{self, AppError},
     messages,
+    setup::Setup,
     state::State,
-    utils::{self, debug_info::DebugInfo, exec},
+    utils::{
+        self,
+        diagnosis::{self, AutomatedFix},
+        exec, output,
+        release::{self, VersionStatus},
+    },
 };
 use messages::MessageType;
 
@@ -37,29 +42,11 @@ pub struct ActionsMenuPhase<
 {
     provider_remote: FillProvider<F, P, T, N>,
     provider_local: FillProvider<F, P, T, N>,
-    client: reqwest::Client,
     discord_webhook_url: String,
+    release_feed_url: reqwest::Url,
     pub quit: bool,
 }
 
-#[derive(Deserialize)]
-#[serde(untagged)]
-enum DiscordResponse {
-    Success(DiscordSuccessResponse),
-    Error(DiscordError),
-}
-
-#[derive(Deserialize)]
-struct DiscordSuccessResponse {
-    timestamp: DateTime<Utc>,
-}
-
-#[derive(Deserialize)]
-struct DiscordError {
-    code: i64,
-    message: String,
-}
-
 impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone>
     ActionsMenuPhase<F, P, T, N>
 where
@@ -67,39 +54,37 @@ where
 {
     pub fn new(
         discord_webhook_url: String,
+        release_feed_url: reqwest::Url,
         provider_remote: FillProvider<F, P, T, N>,
         provider_local: FillProvider<F, P, T, N>,
     ) -> Self {
         Self {
             quit: false,
             discord_webhook_url,
-            client: reqwest::Client::new(),
+            release_feed_url,
             provider_remote,
             provider_local,
         }
     }
 
-    async fn check_sync(&self) -> Result<(), AppError> {
+    async fn sync_status(&self) -> Result<MessageType<'static>, AppError> {
         match self.provider_local.syncing().await? {
-            SyncStatus::Info(info) => {
-                cliclack::note(
-                    "Sync check",
-                    MessageType::Syncing {
-                        progress: info
-                            .current_block
-                            .saturating_sub(info.starting_block)
-                            .saturating_mul(U256::from(100))
-                            .checked_div(info.highest_block.saturating_sub(info.starting_block))
-                            .unwrap_or(U256::from(100))
-                            .try_into()
-                            .map_err(anyhow::Error::from)?,
-                    },
-                )?;
-            }
-            SyncStatus::None => {
-                cliclack::note("Sync check", MessageType::NotSyncing)?;
-            }
+            SyncStatus::Info(info) => Ok(MessageType::Syncing {
+                progress: info
+                    .current_block
+                    .saturating_sub(info.starting_block)
+                    .saturating_mul(U256::from(100))
+                    .checked_div(info.highest_block.saturating_sub(info.starting_block))
+                    .unwrap_or(U256::from(100))
+                    .try_into()
+                    .map_err(anyhow::Error::from)?,
+            }),
+            SyncStatus::None => Ok(MessageType::NotSyncing),
         }
+    }
+
+    async fn check_sync(&self) -> Result<(), AppError> {
+        output::note("Sync check", self.sync_status().await?)?;
 
         Ok(())
     }
@@ -150,11 +135,19 @@ where
     }
 
     async fn fix_fork(&self) -> Result<(), AppError> {
-        cliclack::log::step(MessageType::FixForkStepFixing)?;
+        output::log::step(MessageType::FixForkStepFixing)?;
+
+        self.restore_chain_from_backup().await?;
+
+        output::log::step(MessageType::FixForkStepFixed)?;
 
+        Ok(())
+    }
+
+    async fn restore_chain_from_backup(&self) -> Result<(), AppError> {
         exec::run_docker_compose_down()?;
 
-        cliclack::log::step(MessageType::FixForkStepRemovingChains)?;
+        output::log::step(MessageType::FixForkStepRemovingChains)?;
 
         tokio::fs::remove_dir_all(utils::output_dir().join("./chains"))
             .await
@@ -166,122 +159,154 @@ where
                 }
             })?;
 
-        cliclack::log::step(MessageType::FixForkStepDownloadingBackup)?;
+        output::log::step(MessageType::FixForkStepDownloadingBackup)?;
 
         exec::run_download_backup("https://backup.ambrosus.io/blockchain.tgz").await?;
 
-        exec::run_docker_compose_up()?;
+        exec::run_docker_compose_up()
+    }
+
+    async fn regenerate_keystore(&self) -> Result<(), AppError> {
+        output::log::step(MessageType::FixKeystoreStepRegenerating)?;
+
+        Setup::new(State::read()?)?.rotate_keystore().await?;
+
+        exec::run_docker_compose_down()?;
+        exec::run_docker_compose_up()
+    }
+
+    async fn check_logs(&self) -> Result<(), AppError> {
+        let findings = diagnosis::diagnose(&exec::get_docker_compose_logs());
+        if findings.is_empty() {
+            output::note("Logs check", MessageType::LogsCheckOk)?;
+            return Ok(());
+        }
 
-        cliclack::log::step(MessageType::FixForkStepFixed)?;
+        for finding in findings {
+            output::note(
+                "Logs check",
+                MessageType::DiagnosisFinding {
+                    explanation: finding.issue.explanation().to_string(),
+                    occurrences: finding.occurrences,
+                    sample: finding.sample,
+                },
+            )?;
+
+            let Some(fix) = finding.fix else {
+                continue;
+            };
+
+            if cliclack::confirm(MessageType::AskApplyAutomatedFix).interact()? {
+                match fix {
+                    AutomatedFix::RestoreChainFromBackup => {
+                        output::log::step(MessageType::FixForkStepFixing)?;
+                        self.restore_chain_from_backup().await?;
+                        output::log::step(MessageType::FixForkStepFixed)?;
+                    }
+                    AutomatedFix::RegenerateKeystore => self.regenerate_keystore().await?,
+                }
+            }
+        }
 
         Ok(())
     }
 
-    async fn check_git_version(&self) -> MessageType {
-        let (local, remote) = exec::get_git_commits().await;
+    async fn check_nop_version(&self) -> Result<(), AppError> {
+        let current = release::current_version();
+        let status = async {
+            release::fetch_latest(&self.release_feed_url)
+                .await?
+                .version_status(&current)
+        }
+        .await;
+
+        let status = match status {
+            Ok(status) => status,
+            // Release feed outage must not break the rest of the checks
+            Err(e) => {
+                output::note(
+                    "NOP version check",
+                    MessageType::NopVersionUnknown {
+                        reason: e.to_string(),
+                    },
+                )?;
+                return Ok(());
+            }
+        };
+
+        let message = match &status {
+            VersionStatus::UpToDate => MessageType::NopVersionOk { version: &current },
+            VersionStatus::Outdated {
+                latest,
+                critical: false,
+            } => MessageType::NopVersionOutdated {
+                current: &current,
+                latest,
+            },
+            VersionStatus::Outdated {
+                latest,
+                critical: true,
+            } => MessageType::NopVersionCriticalUpdate {
+                current: &current,
+                latest,
+            },
+            VersionStatus::Unsupported {
+                latest,
+                min_supported,
+            } => MessageType::NopVersionUnsupported {
+                current: &current,
+                min_supported,
+                latest,
+            },
+        };
+        output::note("NOP version check", &message)?;
 
-        if local == remote {
-            MessageType::GitVersionOk
-        } else {
-            MessageType::GitVersionOld { local, remote }
+        if status != VersionStatus::UpToDate
+            && cliclack::confirm(MessageType::AskFixNopVersionIssue).interact()?
+        {
+            self.fix_nop_version().await?;
         }
+
+        Ok(())
     }
 
-    async fn fix_git_version(&self) -> Result<(), AppError> {
-        cliclack::log::step(MessageType::FixGitVersionStepUpdate)?;
+    async fn fix_nop_version(&self) -> Result<(), AppError> {
+        output::log::step(MessageType::FixNopVersionStepUpdate)?;
+
+        let mut self_update = SelfUpdatePhase::new(self.release_feed_url.clone(), false);
+        self_update.run().await?;
 
-        exec::run_update(PathBuf::from("./update.sh")).await?;
+        // The running process is the old binary
+        if self_update.updated {
+            std::process::exit(0)
+        }
 
-        std::process::exit(0)
+        Ok(())
     }
 
     async fn check(&self) -> Result<(), AppError> {
         self.check_sync().await?;
 
+        IpChangeCheckPhase::new(false).run().await?;
+
+        let state = State::read()?;
+        if let Some(ip) = state.ip {
+            P2pCheckPhase::new(self.provider_local.clone(), ip, state.ports().p2p)
+                .run()
+                .await?;
+        }
+
         let fork_status = self.check_fork().await?;
-        cliclack::note("Fork check", &fork_status)?;
+        output::note("Fork check", &fork_status)?;
         if fork_status == MessageType::Forked
             && cliclack::confirm(MessageType::AskFixForkIssue).interact()?
         {
             self.fix_fork().await?;
         }
 
-        let git_versiom_status = self.check_git_version().await;
-        cliclack::note("Git version check", &git_versiom_status)?;
-        if let MessageType::GitVersionOld { .. } = git_versiom_status {
-            if cliclack::confirm(MessageType::AskFixGitVersionIssue).interact()? {
-                self.fix_git_version().await?;
-            }
-        }
+        self.check_logs().await?;
 
-        Ok(())
-    }
-
-    async fn send_logs(&self) -> Result<(), AppError> {
-        let State {
-            ip: Some(ip_address),
-            ..
-        } = State::read()?
-        else {
-            return Err(anyhow!("IP configuration is missed in state").into());
-        };
-
-        let node_version = exec::get_node_version();
-        let debug_info = DebugInfo::collect().await?;
-        let title = format!("{:?}-{}", debug_info.address, debug_info.timestamp);
-
-        let debug_info_payload = format!("{debug_info:?}");
-        let form = reqwest::multipart::Form::new()
-            .part(
-                "payload_json",
-                reqwest::multipart::Part::text(serde_json::to_string(
-                    &serde_json::json!({
-                        "content": format!("
-                            node version: {node_version}
-                            address: {ip_address}
-                            network: {network_name}
-                            ETH address: {eth_address:?}
-                        ", network_name = debug_info.network_name(), eth_address = debug_info.address)
-                    }),
-                )?).mime_str("application/json")?,
-            )
-            .part(
-                format!("Logs {title}"),
-                reqwest::multipart::Part::stream(debug_info_payload).mime_str("text/plain")?
-                    .file_name(format!("{title}.txt")),
-            );
-
-        let req = self
-            .client
-            .post(&self.discord_webhook_url)
-            .multipart(form)
-            .build()?;
-
-        let text = self.client.execute(req).await?.text().await?;
-
-        match serde_json::from_str::<DiscordResponse>(&text) {
-            Ok(DiscordResponse::Success(DiscordSuccessResponse { timestamp })) => {
-                cliclack::note("Send logs", MessageType::LogsReceivedAt { timestamp })?;
-            }
-            Ok(DiscordResponse::Error(DiscordError { code, message })) => {
-                cliclack::note(
-                    "Send logs",
-                    MessageType::LogsSendError {
-                        msg: format!("Code: {code} Message: {message}"),
-                    },
-                )?;
-            }
-            Err(_) => {
-                cliclack::note(
-                    "Send logs",
-                    MessageType::LogsSendError {
-                        msg: format!("Failed to parse: {text}"),
-                    },
-                )?;
-            }
-        }
-
-        Ok(())
+        self.check_nop_version().await
     }
 }
 
@@ -296,6 +321,7 @@ where
                 .items(
                     &([
                         MessageType::LogsActionMenuItem,
+                        MessageType::ViewLogsActionMenuItem,
                         MessageType::CheckActionMenuItem,
                         MessageType::QuitActionMenuItem,
                     ]
@@ -313,10 +339,97 @@ where
                     self.quit = true;
                     Ok(())
                 }
-                MessageType::LogsActionMenuItem => self.send_logs().await.map_err(AppError::from),
+                MessageType::LogsActionMenuItem => {
+                    DebugReportPhase::send(self.discord_webhook_url.clone())
+                        .run()
+                        .await
+                }
+                MessageType::ViewLogsActionMenuItem => ViewLogsPhase::new(None).run().await,
                 _ => self.check().await.map_err(AppError::from),
             }
         }
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rpc_mock::{self, MockRpcServer};
+    use alloy::{network::AnyNetwork, providers::ProviderBuilder};
+    use serde_json::json;
+
+    const TIMESTAMP: u64 = 1_730_455_200;
+
+    #[tokio::test]
+    async fn test_sync_and_fork() {
+        let (local, remote) = (MockRpcServer::start().await, MockRpcServer::start().await);
+        let actions_menu = ActionsMenuPhase::new(
+            String::new(),
+            "http://127.0.0.1/releases/latest".parse().unwrap(),
+            ProviderBuilder::new()
+                .with_recommended_fillers()
+                .network::<AnyNetwork>()
+                .on_http(remote.url()),
+            ProviderBuilder::new()
+                .with_recommended_fillers()
+                .network::<AnyNetwork>()
+                .on_http(local.url()),
+        );
+
+        local.respond("eth_syncing", json!(false));
+        assert_eq!(
+            actions_menu.sync_status().await.unwrap(),
+            MessageType::NotSyncing
+        );
+        local.respond(
+            "eth_syncing",
+            json!({ "startingBlock": "0x64", "currentBlock": "0x96", "highestBlock": "0xc8" }),
+        );
+        assert_eq!(
+            actions_menu.sync_status().await.unwrap(),
+            MessageType::Syncing { progress: 50 }
+        );
+        // Nothing to import yet
+        local.respond(
+            "eth_syncing",
+            json!({ "startingBlock": "0x64", "currentBlock": "0x64", "highestBlock": "0x64" }),
+        );
+        assert_eq!(
+            actions_menu.sync_status().await.unwrap(),
+            MessageType::Syncing { progress: 100 }
+        );
+
+        local.respond_block("latest", rpc_mock::block(100, 0xaa, TIMESTAMP));
+        remote
+            .respond_block("0x64", rpc_mock::block(100, 0xaa, TIMESTAMP))
+            .respond_block("latest", rpc_mock::block(105, 0xbb, TIMESTAMP + 25));
+        assert_eq!(
+            actions_menu.check_fork().await.unwrap(),
+            MessageType::NotForked
+        );
+
+        // Node is too far behind the network
+        remote.respond_block("latest", rpc_mock::block(200, 0xbb, TIMESTAMP + 500));
+        assert_eq!(
+            actions_menu.check_fork().await.unwrap(),
+            MessageType::Forked
+        );
+
+        // Block at the same height differs from the network one
+        remote
+            .respond_block("0x64", rpc_mock::block(100, 0xcc, TIMESTAMP))
+            .respond_block("latest", rpc_mock::block(105, 0xbb, TIMESTAMP + 25));
+        assert_eq!(
+            actions_menu.check_fork().await.unwrap(),
+            MessageType::Forked
+        );
+
+        remote.respond_block("0x64", rpc_mock::block(100, 0xaa, TIMESTAMP));
+        local.respond_block("latest", json!(null));
+        assert_eq!(
+            actions_menu.check_fork().await.unwrap(),
+            MessageType::Forked
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use alloy::{
+    primitives::U64,
+    providers::{
+        fillers::{FillProvider, TxFiller},
+        Network, Provider,
+    },
+    transports::Transport,
+};
+use futures_util::{future::BoxFuture, FutureExt};
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    utils::{exec, output, profile},
+};
+use messages::MessageType;
+
+pub const PARITY_CONTAINER_NAME: &str = "parity";
+pub const ETHSTATS_CONTAINER_NAME: &str = "ethstats_client";
+
+const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(300);
+const HEALTH_CHECK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MIN_PEERS_COUNT: u64 = 1;
+
+pub struct HealthCheckPhase<
+    F,
+    P: Provider<T, N> + Send + Sync + Clone,
+    T: Transport + Clone,
+    N: Network + Clone,
+> where
+    F: TxFiller<N>,
+{
+    provider_local: FillProvider<F, P, T, N>,
+    expected_chain_id: u64,
+    timeout: Duration,
+    /// How long the node must stay healthy and keep importing blocks after it became healthy
+    grace_period: Duration,
+    pub report: HealthReport,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct HealthReport {
+    pub parity_status: Option<String>,
+    pub ethstats_status: Option<String>,
+    pub chain_id: Option<u64>,
+    pub expected_chain_id: u64,
+    pub peers_count: Option<u64>,
+    pub block_number: Option<u64>,
+    /// Block number didn't advance during the grace period
+    pub stalled: bool,
+    /// Errors of the failed RPC calls, prefixed with the method
+    pub rpc_errors: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.is_parity_running()
+            && self.is_ethstats_running()
+            && self.chain_id == Some(self.expected_chain_id)
+            && self.has_peers()
+            && !self.stalled
+    }
+
+    fn is_parity_running(&self) -> bool {
+        self.parity_status.as_deref() == Some("running")
+    }
+
+    fn is_ethstats_running(&self) -> bool {
+        self.ethstats_status.as_deref() == Some("running")
+    }
+
+    fn has_peers(&self) -> bool {
+        matches!(self.peers_count, Some(count) if count >= MIN_PEERS_COUNT)
+    }
+}
+
+impl Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mark = |ok: bool| if ok { "✅" } else { "⛔" };
+        let status = |status: &Option<String>| {
+            status
+                .clone()
+                .unwrap_or_else(|| MessageType::StatusNotFound.to_string())
+        };
+
+        writeln!(
+            f,
+            "{} {}",
+            mark(self.is_parity_running()),
+            MessageType::HealthContainerStatus {
+                container: PARITY_CONTAINER_NAME,
+                status: status(&self.parity_status),
+            }
+        )?;
+        writeln!(
+            f,
+            "{} {}",
+            mark(self.is_ethstats_running()),
+            MessageType::HealthContainerStatus {
+                container: ETHSTATS_CONTAINER_NAME,
+                status: status(&self.ethstats_status),
+            }
+        )?;
+        match self.chain_id {
+            Some(chain_id) => writeln!(
+                f,
+                "{} {}",
+                mark(chain_id == self.expected_chain_id),
+                MessageType::HealthChainId {
+                    chain_id,
+                    expected: self.expected_chain_id,
+                }
+            )?,
+            None => writeln!(
+                f,
+                "{} {}",
+                mark(false),
+                MessageType::HealthChainIdUnavailable {
+                    expected: self.expected_chain_id,
+                }
+            )?,
+        }
+        match self.peers_count {
+            Some(count) => write!(
+                f,
+                "{} {}",
+                mark(self.has_peers()),
+                MessageType::PeersConnected { count }
+            )?,
+            None => write!(f, "{} {}", mark(false), MessageType::PeersConnectedUnknown)?,
+        }
+        if self.stalled {
+            let stalled = match self.block_number {
+                Some(block) => MessageType::HealthImportStalled { block },
+                None => MessageType::HealthImportStalledUnknownBlock,
+            };
+            write!(f, "\n{} {stalled}", mark(false))?;
+        }
+        for error in &self.rpc_errors {
+            write!(f, "\n{}", MessageType::HealthRpcError { error })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone>
+    HealthCheckPhase<F, P, T, N>
+where
+    F: TxFiller<N>,
+{
+    pub fn new(provider_local: FillProvider<F, P, T, N>, expected_chain_id: u64) -> Self {
+        Self {
+            provider_local,
+            expected_chain_id,
+            timeout: std::env::var("HEALTH_CHECK_TIMEOUT")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_HEALTH_CHECK_TIMEOUT),
+            grace_period: Duration::ZERO,
+            report: HealthReport {
+                expected_chain_id,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Keeps probing the healthy node during the grace period, so a crash loop or a node which
+    /// doesn't import blocks is detected
+    async fn verify_grace_period(&mut self) {
+        let start_block_number = self.report.block_number;
+        let started_at = Instant::now();
+
+        while started_at.elapsed() < self.grace_period {
+            tokio::time::sleep(HEALTH_CHECK_POLL_INTERVAL).await;
+
+            self.report = self.probe().await;
+            if !self.report.is_healthy() {
+                return;
+            }
+        }
+
+        self.report.stalled = self.report.block_number <= start_block_number;
+    }
+
+    async fn probe(&self) -> HealthReport {
+        let mut report = HealthReport {
+            parity_status: exec::get_container_status(
+                &profile::active().container_name(PARITY_CONTAINER_NAME),
+            ),
+            ethstats_status: exec::get_container_status(
+                &profile::active().container_name(ETHSTATS_CONTAINER_NAME),
+            ),
+            expected_chain_id: self.expected_chain_id,
+            ..Default::default()
+        };
+
+        match self.provider_local.get_chain_id().await {
+            Ok(chain_id) => report.chain_id = Some(chain_id),
+            Err(e) => report.rpc_errors.push(format!("eth_chainId: {e}")),
+        }
+
+        match self.provider_local.get_block_number().await {
+            Ok(block_number) => report.block_number = Some(block_number),
+            Err(e) => report.rpc_errors.push(format!("eth_blockNumber: {e}")),
+        }
+
+        match self
+            .provider_local
+            .raw_request::<(), U64>("net_peerCount".into(), ())
+            .await
+        {
+            Ok(count) => report.peers_count = Some(count.to::<u64>()),
+            Err(e) => report.rpc_errors.push(format!("net_peerCount: {e}")),
+        }
+
+        report
+    }
+}
+
+impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone> Phase
+    for HealthCheckPhase<F, P, T, N>
+where
+    F: TxFiller<N>,
+{
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let spinner = output::spinner();
+            spinner.start(MessageType::HealthChecking);
+
+            let started_at = Instant::now();
+            loop {
+                self.report = self.probe().await;
+
+                if self.report.is_healthy() || started_at.elapsed() >= self.timeout {
+                    break;
+                }
+
+                tokio::time::sleep(HEALTH_CHECK_POLL_INTERVAL).await;
+            }
+
+            let became_healthy = self.report.is_healthy();
+            if became_healthy && !self.grace_period.is_zero() {
+                spinner.set_message(MessageType::HealthCheckGracePeriod {
+                    grace_period: self.grace_period,
+                });
+                self.verify_grace_period().await;
+            }
+
+            if self.report.is_healthy() {
+                spinner.stop(MessageType::HealthCheckPassed);
+            } else {
+                if became_healthy {
+                    spinner.error(MessageType::HealthCheckUnstable {
+                        grace_period: self.grace_period,
+                    });
+                } else {
+                    spinner.error(MessageType::HealthCheckTimedOut {
+                        timeout: self.timeout,
+                    });
+                }
+                output::note(
+                    "Health check",
+                    MessageType::HealthCheckFailed {
+                        summary: self.report.to_string(),
+                    },
+                )?;
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_report_is_healthy() {
+        let healthy = HealthReport {
+            parity_status: Some("running".to_owned()),
+            ethstats_status: Some("running".to_owned()),
+            chain_id: Some(16718),
+            expected_chain_id: 16718,
+            peers_count: Some(3),
+            block_number: Some(100),
+            stalled: false,
+            rpc_errors: Vec::new(),
+        };
+        assert!(healthy.is_healthy());
+
+        assert!(!HealthReport {
+            chain_id: Some(22040),
+            ..healthy.clone()
+        }
+        .is_healthy());
+        assert!(!HealthReport {
+            peers_count: Some(0),
+            ..healthy.clone()
+        }
+        .is_healthy());
+        assert!(!HealthReport {
+            parity_status: Some("restarting".to_owned()),
+            ..healthy.clone()
+        }
+        .is_healthy());
+        assert!(!HealthReport {
+            stalled: true,
+            ..healthy.clone()
+        }
+        .is_healthy());
+        assert!(!HealthReport {
+            ethstats_status: None,
+            ..healthy
+        }
+        .is_healthy());
+    }
+}
@@ -0,0 +1,380 @@
+use anyhow::anyhow;
+use futures_util::{future::BoxFuture, FutureExt};
+use std::{
+    fmt::Display,
+    net::{Ipv4Addr, SocketAddr, TcpListener, UdpSocket},
+    time::Duration,
+};
+
+use super::{check_health::PARITY_CONTAINER_NAME, Phase};
+use crate::{
+    error::AppError,
+    messages,
+    utils::{
+        self, exec, ntp, output,
+        profile::{self, NodePorts},
+        sysinfo,
+    },
+};
+use messages::MessageType;
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+const MIN_CPU_COUNT: usize = 1;
+const RECOMMENDED_CPU_COUNT: usize = 2;
+const MIN_MEMORY_KB: u64 = 2 * 1024 * 1024;
+const RECOMMENDED_MEMORY_KB: u64 = 4 * 1024 * 1024;
+const MIN_DISK_BYTES: u64 = 20 * GIB;
+const RECOMMENDED_DISK_BYTES: u64 = 100 * GIB;
+const MIN_INODES: u64 = 100_000;
+const RECOMMENDED_INODES: u64 = 1_000_000;
+const MIN_KERNEL_MAJOR_VERSION: u32 = 4;
+/// Supported distributions with minimal major version
+const SUPPORTED_DISTRIBUTIONS: [(&str, u32); 2] = [("ubuntu", 20), ("debian", 11)];
+
+const NTP_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_CLOCK_OFFSET_WARN_SECS: f64 = 0.5;
+const MAX_CLOCK_OFFSET_FAIL_SECS: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "{}", MessageType::HostStatusPass),
+            CheckStatus::Warn => write!(f, "{}", MessageType::HostStatusWarn),
+            CheckStatus::Fail => write!(f, "{}", MessageType::HostStatusFail),
+        }
+    }
+}
+
+pub struct HostCheck {
+    pub name: MessageType<'static>,
+    pub status: CheckStatus,
+    pub value: String,
+    pub hint: Option<MessageType<'static>>,
+}
+
+impl HostCheck {
+    fn new(
+        name: MessageType<'static>,
+        status: CheckStatus,
+        value: String,
+        hint: MessageType<'static>,
+    ) -> Self {
+        Self {
+            name,
+            status,
+            value,
+            hint: (status != CheckStatus::Pass).then_some(hint),
+        }
+    }
+}
+
+/// Verifies host resources and environment before any container is started
+pub struct HostRequirementsPhase {
+    /// Ports the node is going to publish
+    ports: NodePorts,
+    pub checks: Vec<HostCheck>,
+}
+
+impl HostRequirementsPhase {
+    pub fn new(ports: NodePorts) -> Self {
+        Self {
+            ports,
+            checks: Vec::new(),
+        }
+    }
+
+    fn check_cpu() -> HostCheck {
+        let count = std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1);
+
+        HostCheck::new(
+            MessageType::HostCheckCpu,
+            threshold_status(count, MIN_CPU_COUNT, RECOMMENDED_CPU_COUNT),
+            MessageType::HostValueCores { count }.to_string(),
+            MessageType::HostHintCpu {
+                recommended: RECOMMENDED_CPU_COUNT,
+            },
+        )
+    }
+
+    fn check_memory() -> HostCheck {
+        match sysinfo::memory_info() {
+            Ok(memory) => HostCheck::new(
+                MessageType::HostCheckMemory,
+                threshold_status(memory.total_kb, MIN_MEMORY_KB, RECOMMENDED_MEMORY_KB),
+                MessageType::HostValueMemory {
+                    gib: format!("{:.1}", memory.total_kb as f64 / (1024.0 * 1024.0)),
+                }
+                .to_string(),
+                MessageType::HostHintMemory {
+                    recommended_gib: RECOMMENDED_MEMORY_KB / (1024 * 1024),
+                },
+            ),
+            Err(e) => unknown_check(MessageType::HostCheckMemory, e),
+        }
+    }
+
+    fn check_disk() -> [HostCheck; 2] {
+        let output_dir = utils::output_dir();
+        // Output directory may not exist yet, so check the closest existing parent
+        let path = output_dir
+            .ancestors()
+            .find(|path| path.exists())
+            .unwrap_or(&output_dir)
+            .to_owned();
+
+        match sysinfo::disk_usage(&path) {
+            Ok(disk) => [
+                HostCheck::new(
+                    MessageType::HostCheckDisk,
+                    threshold_status(disk.available_bytes, MIN_DISK_BYTES, RECOMMENDED_DISK_BYTES),
+                    MessageType::HostValueDiskFree {
+                        gib: format!("{:.1}", disk.available_bytes as f64 / GIB as f64),
+                    }
+                    .to_string(),
+                    MessageType::HostHintDisk {
+                        recommended_gib: RECOMMENDED_DISK_BYTES / GIB,
+                    },
+                ),
+                HostCheck::new(
+                    MessageType::HostCheckInodes,
+                    threshold_status(disk.available_inodes, MIN_INODES, RECOMMENDED_INODES),
+                    MessageType::HostValueInodesFree {
+                        count: disk.available_inodes,
+                    }
+                    .to_string(),
+                    MessageType::HostHintInodes,
+                ),
+            ],
+            Err(e) => [
+                unknown_check(MessageType::HostCheckDisk, e.to_string()),
+                unknown_check(MessageType::HostCheckInodes, e),
+            ],
+        }
+    }
+
+    fn check_ports(ports: &NodePorts) -> Vec<HostCheck> {
+        // Ports are expected to be busy if our own node is already running
+        let parity_running =
+            exec::get_container_status(&profile::active().container_name(PARITY_CONTAINER_NAME))
+                .as_deref()
+                == Some("running");
+
+        [
+            (MessageType::HostCheckP2pTcpPort, ports.p2p, false),
+            (MessageType::HostCheckP2pUdpPort, ports.p2p, true),
+            (MessageType::HostCheckRpcPort, ports.rpc, false),
+        ]
+        .into_iter()
+        .map(|(name, port, udp)| {
+            let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+            let available = if udp {
+                UdpSocket::bind(address).is_ok()
+            } else {
+                TcpListener::bind(address).is_ok()
+            };
+
+            let (status, value) = match (available, parity_running) {
+                (true, _) => (
+                    CheckStatus::Pass,
+                    MessageType::HostValuePortAvailable { port },
+                ),
+                (false, true) => (
+                    CheckStatus::Pass,
+                    MessageType::HostValuePortUsedByParity { port },
+                ),
+                (false, false) => (CheckStatus::Fail, MessageType::HostValuePortInUse { port }),
+            };
+
+            HostCheck::new(
+                name,
+                status,
+                value.to_string(),
+                MessageType::HostHintPort { port },
+            )
+        })
+        .collect()
+    }
+
+    async fn check_clock() -> HostCheck {
+        match ntp::clock_offset(ntp::DEFAULT_NTP_SERVER, NTP_TIMEOUT).await {
+            Ok(offset) => HostCheck::new(
+                MessageType::HostCheckClock,
+                if offset.abs() >= MAX_CLOCK_OFFSET_FAIL_SECS {
+                    CheckStatus::Fail
+                } else if offset.abs() >= MAX_CLOCK_OFFSET_WARN_SECS {
+                    CheckStatus::Warn
+                } else {
+                    CheckStatus::Pass
+                },
+                MessageType::HostValueClockOffset {
+                    offset: format!("{offset:+.3}s"),
+                }
+                .to_string(),
+                MessageType::HostHintClock,
+            ),
+            Err(e) => HostCheck::new(
+                MessageType::HostCheckClock,
+                CheckStatus::Warn,
+                MessageType::HostValueUnknown {
+                    error: e.to_string(),
+                }
+                .to_string(),
+                MessageType::HostHintClock,
+            ),
+        }
+    }
+
+    fn check_os() -> [HostCheck; 2] {
+        let os_release = match sysinfo::os_release() {
+            Ok(os_release) => os_release,
+            Err(e) => {
+                return [
+                    unknown_check(MessageType::HostCheckDistribution, e.to_string()),
+                    unknown_check(MessageType::HostCheckKernel, e),
+                ]
+            }
+        };
+
+        let distribution_supported = SUPPORTED_DISTRIBUTIONS.iter().any(|(id, min_major)| {
+            *id == os_release.id && major_version(&os_release.version_id) >= Some(*min_major)
+        });
+        let kernel_supported = major_version(&os_release.kernel) >= Some(MIN_KERNEL_MAJOR_VERSION);
+
+        [
+            HostCheck::new(
+                MessageType::HostCheckDistribution,
+                if distribution_supported {
+                    CheckStatus::Pass
+                } else {
+                    CheckStatus::Warn
+                },
+                os_release.pretty_name,
+                MessageType::HostHintDistribution,
+            ),
+            HostCheck::new(
+                MessageType::HostCheckKernel,
+                if kernel_supported {
+                    CheckStatus::Pass
+                } else {
+                    CheckStatus::Fail
+                },
+                os_release.kernel,
+                MessageType::HostHintKernel {
+                    min_version: MIN_KERNEL_MAJOR_VERSION,
+                },
+            ),
+        ]
+    }
+
+    fn render_table(&self) -> String {
+        let names = self
+            .checks
+            .iter()
+            .map(|check| check.name.to_string())
+            .collect::<Vec<_>>();
+        let name_width = names
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or_default();
+        let value_width = self
+            .checks
+            .iter()
+            .map(|check| check.value.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        self.checks
+            .iter()
+            .zip(names)
+            .map(|(check, name)| {
+                let row = format!(
+                    "{} {name:name_width$}  {:value_width$}",
+                    check.status, check.value
+                );
+                match &check.hint {
+                    Some(hint) => format!("{row}\n   ↳ {hint}"),
+                    None => row,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Phase for HostRequirementsPhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            self.checks = [Self::check_cpu(), Self::check_memory()]
+                .into_iter()
+                .chain(Self::check_disk())
+                .chain(Self::check_ports(&self.ports))
+                .chain([Self::check_clock().await])
+                .chain(Self::check_os())
+                .collect();
+
+            output::note("Host requirements check", self.render_table())?;
+
+            match self.checks.iter().map(|check| check.status).max() {
+                Some(CheckStatus::Fail) => {
+                    Err(anyhow!("{}", MessageType::HostRequirementsFailed).into())
+                }
+                _ => Ok(()),
+            }
+        }
+        .boxed()
+    }
+}
+
+fn threshold_status<T: PartialOrd>(value: T, min: T, recommended: T) -> CheckStatus {
+    if value < min {
+        CheckStatus::Fail
+    } else if value < recommended {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    }
+}
+
+fn unknown_check(name: MessageType<'static>, error: impl Display) -> HostCheck {
+    HostCheck {
+        name,
+        status: CheckStatus::Warn,
+        value: MessageType::HostValueUnknown {
+            error: error.to_string(),
+        }
+        .to_string(),
+        hint: None,
+    }
+}
+
+fn major_version(version: &str) -> Option<u32> {
+    version.split(['.', '-']).next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(threshold_status(1, 2, 4), CheckStatus::Fail);
+        assert_eq!(threshold_status(2, 2, 4), CheckStatus::Warn);
+        assert_eq!(threshold_status(4, 2, 4), CheckStatus::Pass);
+
+        assert_eq!(major_version("22.04"), Some(22));
+        assert_eq!(major_version("12"), Some(12));
+        assert_eq!(major_version("6.8.0-45-generic"), Some(6));
+        assert_eq!(major_version(""), None);
+    }
+}
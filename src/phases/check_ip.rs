@@ -0,0 +1,177 @@
+use futures_util::{future::BoxFuture, FutureExt};
+use std::net::IpAddr;
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    setup::Setup,
+    state::State,
+    utils::{
+        exec,
+        logs::PARITY_SERVICE_NAME,
+        net::{self, PublicIpDiscovery},
+        output,
+    },
+};
+use messages::MessageType;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpChange {
+    Unchanged,
+    Changed {
+        old: IpAddr,
+        new: IpAddr,
+    },
+    /// Public IP can't be determined reliably, e.g. discovery sources disagree
+    Unknown {
+        reason: String,
+    },
+}
+
+/// Compares the stored node IP with the current public IP and updates the node configuration on change
+pub struct IpChangeCheckPhase {
+    /// Apply changes without asking for confirmation
+    daemon: bool,
+}
+
+impl IpChangeCheckPhase {
+    pub fn new(daemon: bool) -> Self {
+        Self { daemon }
+    }
+
+    async fn detect(stored_ip: IpAddr) -> Result<IpChange, AppError> {
+        let local_addresses = net::local_interface_addresses().unwrap_or_default();
+        // Node IP assigned to the host directly can't change behind our back
+        if local_addresses
+            .iter()
+            .any(|address| IpAddr::V4(address.ip) == stored_ip)
+        {
+            return Ok(IpChange::Unchanged);
+        }
+
+        Ok(compare_ips(stored_ip, &net::discover_public_ips().await?))
+    }
+
+    async fn apply(mut state: State, new_ip: IpAddr) -> Result<(), AppError> {
+        state.ip = Some(new_ip);
+        state.write().await?;
+
+        output::log::step(MessageType::IpChangeStepRegeneratingConfig)?;
+        Setup::new(state)?.regenerate_parity_config().await?;
+
+        output::log::step(MessageType::IpChangeStepRestarting)?;
+        exec::run_docker_compose_restart(PARITY_SERVICE_NAME)
+    }
+}
+
+impl Phase for IpChangeCheckPhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let state = State::read()?;
+            let Some(stored_ip) = state.ip else {
+                output::note("IP check", MessageType::IpChangeNoStoredIp)?;
+                return Ok(());
+            };
+
+            match Self::detect(stored_ip).await? {
+                IpChange::Unchanged => {
+                    output::note(
+                        "IP check",
+                        MessageType::IpChangeNotChanged { ip: stored_ip },
+                    )?;
+                }
+                IpChange::Unknown { reason } => {
+                    output::note("IP check", MessageType::IpChangeUnknown { reason })?;
+                }
+                IpChange::Changed { old, new } => {
+                    output::note("IP check", MessageType::IpChangeDetected { old, new })?;
+
+                    if !self.daemon {
+                        output::require_interactive("--daemon")?;
+                    }
+                    if self.daemon
+                        || cliclack::confirm(MessageType::AskApplyIpChange { new }).interact()?
+                    {
+                        Self::apply(state, new).await?;
+                        output::log::success(MessageType::IpChangeApplied { ip: new })?;
+                    }
+                }
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+fn compare_ips(stored_ip: IpAddr, discovery: &PublicIpDiscovery) -> IpChange {
+    if discovery.contains(&stored_ip) {
+        return IpChange::Unchanged;
+    }
+
+    let mut candidates = discovery.candidates.iter();
+
+    match (candidates.next(), candidates.next()) {
+        (None, _) => IpChange::Unknown {
+            reason: MessageType::IpChangeNoSources.to_string(),
+        },
+        (Some(_), Some(_)) => IpChange::Unknown {
+            reason: MessageType::IpChangeSourcesDisagree.to_string(),
+        },
+        // Require majority of sources to avoid reconfiguring the node because of a single faulty service
+        (Some(candidate), None) if candidate.sources.len() * 2 > discovery.sources_count => {
+            IpChange::Changed {
+                old: stored_ip,
+                new: candidate.ip,
+            }
+        }
+        (Some(candidate), None) => IpChange::Unknown {
+            reason: MessageType::IpChangeNotConfirmed {
+                confirmed: candidate.sources.len(),
+                total: discovery.sources_count,
+                ip: candidate.ip,
+            }
+            .to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_ips() {
+        let old: IpAddr = "203.0.113.7".parse().unwrap();
+        let new: IpAddr = "203.0.113.8".parse().unwrap();
+
+        let discovery = PublicIpDiscovery::new(vec![("ipify", old), ("icanhazip", old)], 3);
+        assert_eq!(compare_ips(old, &discovery), IpChange::Unchanged);
+
+        let discovery = PublicIpDiscovery::new(
+            vec![("ipify", new), ("icanhazip", new), ("ident.me", new)],
+            3,
+        );
+        assert_eq!(compare_ips(old, &discovery), IpChange::Changed { old, new });
+
+        let discovery = PublicIpDiscovery::new(vec![("ipify", new)], 3);
+        assert!(matches!(
+            compare_ips(old, &discovery),
+            IpChange::Unknown { .. }
+        ));
+
+        let discovery = PublicIpDiscovery::new(
+            vec![("ipify", new), ("icanhazip", new), ("ident.me", old)],
+            3,
+        );
+        assert_eq!(compare_ips(old, &discovery), IpChange::Unchanged);
+
+        assert_eq!(
+            compare_ips(old, &PublicIpDiscovery::new(Vec::new(), 3)),
+            IpChange::Unknown {
+                reason: MessageType::IpChangeNoSources.to_string()
+            }
+        );
+    }
+}
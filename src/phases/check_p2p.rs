@@ -0,0 +1,331 @@
+use alloy::{
+    primitives::U64,
+    providers::{
+        fillers::{FillProvider, TxFiller},
+        Network, Provider,
+    },
+    transports::Transport,
+};
+use futures_util::{future::BoxFuture, FutureExt};
+use serde::Deserialize;
+use std::{
+    fmt::Display,
+    net::{IpAddr, SocketAddr, UdpSocket},
+};
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    utils::{net, output},
+};
+use messages::MessageType;
+
+/// How the configured `extip` is reachable from the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpBinding {
+    /// IP is assigned to one of the host interfaces
+    Local,
+    /// IP matches the public IP of the host, so it is reachable only if the port is forwarded
+    Forwarded,
+    /// IP is neither assigned to the host nor matches its public IP
+    Mismatch {
+        public_ip: IpAddr,
+    },
+    Unknown,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NetPeers {
+    pub max: u64,
+    pub peers: Vec<PeerInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PeerInfo {
+    pub network: PeerNetworkInfo,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerNetworkInfo {
+    pub local_address: String,
+}
+
+impl PeerInfo {
+    /// Inbound connections are accepted on the listening port, outbound ones use an ephemeral port
+    fn is_inbound(&self, p2p_port: u16) -> Option<bool> {
+        self.network
+            .local_address
+            .parse::<SocketAddr>()
+            .ok()
+            .map(|address| address.port() == p2p_port)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct P2pReport {
+    pub ip: IpAddr,
+    pub p2p_port: u16,
+    pub binding: IpBinding,
+    pub peers_count: Option<u64>,
+    pub max_peers: Option<u64>,
+    pub inbound: usize,
+    pub outbound: usize,
+    pub rpc_error: Option<String>,
+}
+
+impl P2pReport {
+    fn new(ip: IpAddr, p2p_port: u16, binding: IpBinding) -> Self {
+        Self {
+            ip,
+            p2p_port,
+            binding,
+            peers_count: None,
+            max_peers: None,
+            inbound: 0,
+            outbound: 0,
+            rpc_error: None,
+        }
+    }
+
+    fn add_peers(&mut self, peers_count: u64, net_peers: &NetPeers) {
+        self.peers_count = Some(peers_count);
+        self.max_peers = Some(net_peers.max);
+
+        for peer in &net_peers.peers {
+            match peer.is_inbound(self.p2p_port) {
+                Some(true) => self.inbound += 1,
+                Some(false) => self.outbound += 1,
+                // Peer is still handshaking
+                None => {}
+            }
+        }
+    }
+
+    pub fn verdict(&self) -> MessageType<'static> {
+        if let Some(error) = &self.rpc_error {
+            return MessageType::P2pRpcUnavailable { msg: error.clone() };
+        }
+        if let IpBinding::Mismatch { public_ip } = self.binding {
+            return MessageType::P2pIpMismatch {
+                ip: self.ip,
+                public_ip,
+            };
+        }
+
+        match self.peers_count {
+            Some(0) | None => MessageType::P2pNoPeers {
+                port: self.p2p_port,
+            },
+            Some(_) if self.inbound == 0 => MessageType::P2pNoInboundPeers {
+                ip: self.ip,
+                port: self.p2p_port,
+            },
+            Some(_) => MessageType::P2pReachable,
+        }
+    }
+}
+
+impl Display for P2pReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let binding = match self.binding {
+            IpBinding::Local => MessageType::P2pBindingLocal,
+            IpBinding::Forwarded => MessageType::P2pBindingForwarded,
+            IpBinding::Mismatch { public_ip } => MessageType::P2pBindingMismatch { public_ip },
+            IpBinding::Unknown => MessageType::StatusUnknown,
+        };
+        writeln!(
+            f,
+            "{}",
+            MessageType::P2pExternalIp {
+                ip: self.ip,
+                binding: binding.to_string(),
+            }
+        )?;
+
+        let peers = match (self.peers_count, self.max_peers) {
+            (Some(count), Some(max)) => MessageType::PeersConnectedOf { count, max },
+            (Some(count), None) => MessageType::PeersConnected { count },
+            _ => MessageType::PeersConnectedUnknown,
+        };
+        writeln!(f, "{peers}")?;
+        writeln!(
+            f,
+            "{}",
+            MessageType::P2pInboundPeers {
+                count: self.inbound
+            }
+        )?;
+        writeln!(
+            f,
+            "{}",
+            MessageType::P2pOutboundPeers {
+                count: self.outbound
+            }
+        )?;
+
+        write!(f, "\n{}", self.verdict())
+    }
+}
+
+/// Verifies that peers are able to reach the node on the configured `extip`
+pub struct P2pCheckPhase<
+    F,
+    P: Provider<T, N> + Send + Sync + Clone,
+    T: Transport + Clone,
+    N: Network + Clone,
+> where
+    F: TxFiller<N>,
+{
+    provider_local: FillProvider<F, P, T, N>,
+    ip: IpAddr,
+    p2p_port: u16,
+}
+
+impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone>
+    P2pCheckPhase<F, P, T, N>
+where
+    F: TxFiller<N>,
+{
+    pub fn new(provider_local: FillProvider<F, P, T, N>, ip: IpAddr, p2p_port: u16) -> Self {
+        Self {
+            provider_local,
+            ip,
+            p2p_port,
+        }
+    }
+
+    async fn ip_binding(&self) -> IpBinding {
+        // Binding succeeds only if the address is assigned to one of the interfaces
+        if UdpSocket::bind((self.ip, 0)).is_ok() {
+            return IpBinding::Local;
+        }
+
+        match net::discover_public_ips().await {
+            Ok(discovery) if discovery.contains(&self.ip) => IpBinding::Forwarded,
+            Ok(discovery) => match discovery.best() {
+                Some(public_ip) => IpBinding::Mismatch { public_ip },
+                None => IpBinding::Unknown,
+            },
+            Err(_) => IpBinding::Unknown,
+        }
+    }
+
+    async fn query_peers(&self) -> Result<(u64, NetPeers), AppError> {
+        let peers_count = self
+            .provider_local
+            .raw_request::<(), U64>("net_peerCount".into(), ())
+            .await?;
+        let net_peers = self
+            .provider_local
+            .raw_request::<(), NetPeers>("parity_netPeers".into(), ())
+            .await?;
+
+        Ok((peers_count.to::<u64>(), net_peers))
+    }
+
+    async fn collect_report(&self, binding: IpBinding) -> P2pReport {
+        let mut report = P2pReport::new(self.ip, self.p2p_port, binding);
+
+        match self.query_peers().await {
+            Ok((peers_count, net_peers)) => report.add_peers(peers_count, &net_peers),
+            Err(e) => report.rpc_error = Some(e.to_string()),
+        }
+
+        report
+    }
+}
+
+impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone> Phase
+    for P2pCheckPhase<F, P, T, N>
+where
+    F: TxFiller<N>,
+{
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let binding = self.ip_binding().await;
+            let report = self.collect_report(binding).await;
+
+            output::note("P2P check", report)?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rpc_mock::MockRpcServer;
+    use alloy::{network::AnyNetwork, providers::ProviderBuilder};
+    use serde_json::json;
+
+    fn peer(local_address: &str) -> serde_json::Value {
+        json!({
+            "id": "a1b2",
+            "name": "Parity-Ethereum/v3.3.5",
+            "caps": ["eth/63"],
+            "network": { "localAddress": local_address, "remoteAddress": "203.0.113.7:41234" },
+            "protocols": {},
+        })
+    }
+
+    #[tokio::test]
+    async fn test_p2p_report() {
+        let server = MockRpcServer::start().await;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .network::<AnyNetwork>()
+            .on_http(server.url());
+        let phase = P2pCheckPhase::new(provider, "198.51.100.10".parse().unwrap(), 30303);
+
+        server.respond("net_peerCount", json!("0x3")).respond(
+            "parity_netPeers",
+            json!({
+                "active": 3,
+                "connected": 3,
+                "max": 50,
+                "peers": [peer("172.18.0.2:30303"), peer("172.18.0.2:30303"), peer("172.18.0.2:51820"), peer("Handshake")],
+            }),
+        );
+        let report = phase.collect_report(IpBinding::Forwarded).await;
+        assert_eq!(report.peers_count, Some(3));
+        assert_eq!((report.inbound, report.outbound), (2, 1));
+        assert_eq!(report.verdict(), MessageType::P2pReachable);
+
+        // Only outbound connections mean nobody can dial in, i.e. firewall or NAT issue
+        server.respond(
+            "parity_netPeers",
+            json!({ "active": 1, "connected": 1, "max": 50, "peers": [peer("172.18.0.2:51820")] }),
+        );
+        let report = phase.collect_report(IpBinding::Forwarded).await;
+        assert_eq!(
+            report.verdict(),
+            MessageType::P2pNoInboundPeers {
+                ip: phase.ip,
+                port: 30303
+            }
+        );
+
+        let public_ip = "192.0.2.1".parse().unwrap();
+        let report = phase
+            .collect_report(IpBinding::Mismatch { public_ip })
+            .await;
+        assert_eq!(
+            report.verdict(),
+            MessageType::P2pIpMismatch {
+                ip: phase.ip,
+                public_ip
+            }
+        );
+
+        server.respond("net_peerCount", json!("0x0")).respond(
+            "parity_netPeers",
+            json!({ "active": 0, "connected": 0, "max": 50, "peers": [] }),
+        );
+        let report = phase.collect_report(IpBinding::Local).await;
+        assert_eq!(report.verdict(), MessageType::P2pNoPeers { port: 30303 });
+    }
+}
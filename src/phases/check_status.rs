@@ -16,24 +16,15 @@ use futures_util::{future::BoxFuture, FutureExt};
 use std::{collections::HashMap, time::Duration};
 
 use super::Phase;
-use crate::{config, contract::EthContract, error::AppError, messages};
+use crate::{
+    config,
+    contract::{self, EthContract},
+    error::AppError,
+    messages,
+    utils::output,
+};
 use messages::MessageType;
 
-const DEPLOYMENTS_JSON: [(u64, &str); 3] = [
-    (
-        22040,
-        include_str!("../../airdao-node-contracts/deployments/22040.json"),
-    ),
-    (
-        16718,
-        include_str!("../../airdao-node-contracts/deployments/16718.json"),
-    ),
-    (
-        30746,
-        include_str!("../../airdao-node-contracts/deployments/30746.json"),
-    ),
-];
-
 pub struct CheckStatusPhase<
     F,
     P: Provider<T, N> + Send + Sync + Clone,
@@ -59,24 +50,9 @@ where
         node_addr: Address,
     ) -> Result<Self, AppError> {
         let chain_id = provider.get_chain_id().await?;
-        let mut deployments = DEPLOYMENTS_JSON
-            .iter()
-            .filter_map(|(chain_id, json_text)| {
-                let contracts = serde_json::from_str::<HashMap<String, EthContract>>(json_text);
-                if contracts.is_err() {
-                    println!("{:?}", contracts);
-                }
-                Some((chain_id, contracts.ok()?))
-            })
-            .collect::<HashMap<_, _>>();
         Ok(Self {
+            contracts: contract::deployment(chain_id)?,
             provider,
-            contracts: deployments.remove(&chain_id).ok_or_else(|| {
-                anyhow!(
-                    "Unable to find deployment information for chain id `{}`",
-                    chain_id
-                )
-            })?,
             node_addr,
             explorer_url: network.explorer_url.clone(),
         })
@@ -195,21 +171,35 @@ where
         })
     }
 
+    /// Registration and onboarding status of the node
+    pub async fn node_status(&self) -> Result<NodeStatus, AppError> {
+        match self.get_apollo_info(self.node_addr).await? {
+            ApolloInfo {
+                is_onboarded: true,
+                apollo,
+            } => Ok(NodeStatus::Onboarded {
+                stake: apollo.stake,
+            }),
+            info if !info.is_registered() => Ok(NodeStatus::NotRegistered),
+            info => Ok(NodeStatus::Onboarding {
+                stake: info.apollo.stake,
+                time_to_wait: self
+                    .get_onboarding_waiting_time(self.node_addr, &info.apollo)
+                    .await?,
+            }),
+        }
+    }
+
     async fn get_onboarding_waiting_time(
         &self,
         node_addr: Address,
         stake: &Stake,
     ) -> Result<Duration, AppError> {
-        let now = Utc::now();
-        let seconds_to_wait = u64::try_from(
-            self.get_onboarding_delay(node_addr)
-                .await?
-                .saturating_sub(stake.timestamp_stake),
+        onboarding_waiting_time(
+            stake.timestamp_stake,
+            self.get_onboarding_delay(node_addr).await?,
+            Utc::now().timestamp() as u64,
         )
-        .map_err(anyhow::Error::from)?
-        .saturating_sub(now.timestamp() as u64);
-
-        Ok(Duration::from_secs(seconds_to_wait))
     }
 }
 
@@ -220,11 +210,9 @@ where
 {
     fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
         async {
-            match self.get_apollo_info(self.node_addr).await? {
-                ApolloInfo {
-                    is_onboarded: true, ..
-                } => {
-                    cliclack::note(
+            match self.node_status().await? {
+                NodeStatus::Onboarded { .. } => {
+                    output::note(
                         "Status check",
                         MessageType::NodeOnboarded {
                             explorer_url: &self.explorer_url,
@@ -232,23 +220,16 @@ where
                         },
                     )?;
                 }
-                info if !info.is_registered() => {
-                    cliclack::note(
+                NodeStatus::NotRegistered => {
+                    output::note(
                         "Status check",
                         MessageType::NodeNotRegistered {
                             explorer_url: &self.explorer_url,
                         },
                     )?;
                 }
-                info => {
-                    cliclack::note(
-                        "Status check",
-                        MessageType::NodeOnboarding {
-                            time_to_wait: self
-                                .get_onboarding_waiting_time(self.node_addr, &info.apollo)
-                                .await?,
-                        },
-                    )?;
+                NodeStatus::Onboarding { time_to_wait, .. } => {
+                    output::note("Status check", MessageType::NodeOnboarding { time_to_wait })?;
                 }
             }
 
@@ -258,6 +239,22 @@ where
     }
 }
 
+/// Time left until the node staked at `timestamp_stake` is onboarded, zero if it's overdue
+fn onboarding_waiting_time(
+    timestamp_stake: U256,
+    onboarding_delay: U256,
+    now: u64,
+) -> Result<Duration, AppError> {
+    let seconds_to_wait = u64::try_from(
+        timestamp_stake
+            .saturating_add(onboarding_delay)
+            .saturating_sub(U256::from(now)),
+    )
+    .map_err(anyhow::Error::from)?;
+
+    Ok(Duration::from_secs(seconds_to_wait))
+}
+
 sol! {
     struct StakeInfo {
         uint256 amount;
@@ -286,6 +283,14 @@ sol! {
     }
 }
 
+/// On-chain status of the node, see [`CheckStatusPhase::node_status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeStatus {
+    NotRegistered,
+    Onboarding { stake: U256, time_to_wait: Duration },
+    Onboarded { stake: U256 },
+}
+
 #[derive(Debug)]
 pub struct ApolloInfo {
     apollo: Stake,
@@ -320,3 +325,129 @@ impl From<Lock> for Option<WithdrawLock> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rpc_mock::MockRpcServer;
+    use alloy::{
+        network::AnyNetwork,
+        primitives::{address, utils::parse_ether},
+        providers::ProviderBuilder,
+    };
+    use serde_json::json;
+
+    /// Chain ID of the test network deployment
+    const CHAIN_ID: u64 = 22040;
+    const NODE_ADDR: Address = address!("00000000000000000000000000000000000000a1");
+
+    fn respond_call(server: &MockRpcServer, contract: &str, function: &str, output: Vec<u8>) {
+        let contracts = contract::deployment(CHAIN_ID).unwrap();
+        let contract = &contracts[contract];
+        server.respond_call(
+            contract.address,
+            contract.function(function).unwrap().selector().to_vec(),
+            output,
+        );
+    }
+
+    fn respond_stake(server: &MockRpcServer, stake: U256, timestamp_stake: u64) {
+        let stake = Stake {
+            stake,
+            timestamp_stake: U256::from(timestamp_stake),
+            owner_address: NODE_ADDR,
+            rewards_address: NODE_ADDR,
+        };
+        respond_call(server, "ServerNodesManager", "stakes", stake.abi_encode());
+    }
+
+    async fn node_status(server: &MockRpcServer) -> NodeStatus {
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .network::<AnyNetwork>()
+            .on_http(server.url());
+        let network = config::Network {
+            domain: "test.airdao.io".to_owned(),
+            rpc: server.url(),
+            chainspec: String::new(),
+            explorer_url: "https://airdao.io".to_owned(),
+            name: "test".to_owned(),
+            chain_id: Some(CHAIN_ID),
+        };
+
+        CheckStatusPhase::new(provider, &network, NODE_ADDR)
+            .await
+            .unwrap()
+            .node_status()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_node_status() {
+        let server = MockRpcServer::start().await;
+        server.respond("eth_chainId", json!(format!("{CHAIN_ID:#x}")));
+        let lock = Lock {
+            locker: Address::ZERO,
+            receiver: Address::ZERO,
+            token: Address::ZERO,
+            first_unlock_time: 0,
+            unlock_period: 0,
+            total_claims: 0,
+            times_claimed: 0,
+            interval_amount: U256::ZERO,
+            description: String::new(),
+        };
+        respond_call(&server, "LockKeeper", "getLock", lock.abi_encode());
+        respond_call(
+            &server,
+            "ServerNodesManager",
+            "lockedWithdraws",
+            U256::ZERO.abi_encode(),
+        );
+
+        respond_stake(&server, U256::ZERO, 0);
+        respond_call(
+            &server,
+            "ValidatorSet",
+            "getNodeStake",
+            U256::ZERO.abi_encode(),
+        );
+        assert_eq!(node_status(&server).await, NodeStatus::NotRegistered);
+
+        let stake = parse_ether("1000").unwrap();
+        let now = Utc::now().timestamp() as u64;
+        respond_stake(&server, stake, now - 600);
+        respond_call(
+            &server,
+            "ServerNodesManager",
+            "onboardingDelay",
+            U256::from(3600).abi_encode(),
+        );
+        let NodeStatus::Onboarding {
+            stake: onboarding_stake,
+            time_to_wait,
+        } = node_status(&server).await
+        else {
+            panic!("Node must be onboarding");
+        };
+        assert_eq!(onboarding_stake, stake);
+        assert!((2990..=3000).contains(&time_to_wait.as_secs()));
+
+        respond_call(&server, "ValidatorSet", "getNodeStake", stake.abi_encode());
+        assert_eq!(node_status(&server).await, NodeStatus::Onboarded { stake });
+    }
+
+    #[test]
+    fn test_onboarding_waiting_time() {
+        let waiting_time = |timestamp_stake: u64, delay: u64, now: u64| {
+            onboarding_waiting_time(U256::from(timestamp_stake), U256::from(delay), now)
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(waiting_time(1_000, 3_600, 2_000), 2_600);
+        assert_eq!(waiting_time(1_000, 3_600, 4_600), 0);
+        assert_eq!(waiting_time(1_000, 3_600, 10_000), 0);
+        assert_eq!(waiting_time(1_000, 0, 1_000), 0);
+    }
+}
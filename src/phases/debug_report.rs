@@ -0,0 +1,219 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use futures_util::{future::BoxFuture, FutureExt};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    setup::PASSWORD_FILE_NAME,
+    state::State,
+    utils::{self, debug_info::DebugInfo, exec, output, redact::Redactor},
+};
+use messages::MessageType;
+
+const PREVIEW_MAX_LINES: usize = 40;
+
+pub struct DebugReportPhase {
+    discord_webhook_url: Option<String>,
+    output: Option<PathBuf>,
+    redact_ips: Option<bool>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DiscordResponse {
+    Success(DiscordSuccessResponse),
+    Error(DiscordError),
+}
+
+#[derive(Deserialize)]
+struct DiscordSuccessResponse {
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct DiscordError {
+    code: i64,
+    message: String,
+}
+
+impl DebugReportPhase {
+    /// Creates a phase which sends the report to AirDao support team after operator confirmation
+    pub fn send(discord_webhook_url: String) -> Self {
+        Self {
+            discord_webhook_url: Some(discord_webhook_url),
+            output: None,
+            redact_ips: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Creates a phase which saves the report bundle into `output` file without any prompts
+    pub fn save(output: PathBuf, redact_ips: bool) -> Self {
+        Self {
+            discord_webhook_url: None,
+            output: Some(output),
+            redact_ips: Some(redact_ips),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn redactor(state: &State, redact_ips: bool) -> Redactor {
+        let mut redactor = Redactor::new(redact_ips);
+
+        if let Some(private_key) = &state.private_key {
+            redactor = redactor.with_secret(hex::encode(private_key.to_bytes()));
+        }
+        if let Ok(password) = std::fs::read_to_string(utils::output_dir().join(PASSWORD_FILE_NAME))
+        {
+            redactor = redactor.with_secret(password);
+        }
+        if let (true, Some(ip)) = (redact_ips, state.ip) {
+            redactor = redactor.with_secret(ip.to_string());
+        }
+
+        redactor
+    }
+
+    async fn collect(&self, state: &State) -> Result<(DebugInfo, Redactor), AppError> {
+        let redact_ips = match self.redact_ips {
+            Some(redact_ips) => redact_ips,
+            None => {
+                // Sending requires operator confirmation, reports can only be saved without prompts
+                output::require_interactive("--output")?;
+                cliclack::confirm(MessageType::DebugReportAskRedactIps)
+                    .initial_value(false)
+                    .interact()?
+            }
+        };
+
+        let redactor = Self::redactor(state, redact_ips);
+        let mut debug_info = DebugInfo::collect().await?;
+        debug_info.redact(&redactor);
+
+        Ok((debug_info, redactor))
+    }
+
+    fn preview(debug_info: &DebugInfo) -> Result<String, AppError> {
+        let json = debug_info.to_json()?;
+        let mut preview = json
+            .lines()
+            .take(PREVIEW_MAX_LINES)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if json.lines().count() > PREVIEW_MAX_LINES {
+            preview.push_str("\n...");
+        }
+
+        let contents = debug_info
+            .bundle_contents()?
+            .into_iter()
+            .map(|(name, size)| format!("  {name} ({size} bytes)"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(format!("{preview}\n\n{contents}"))
+    }
+
+    async fn send_to_discord(
+        &self,
+        webhook_url: &str,
+        state: &State,
+        debug_info: &DebugInfo,
+        redactor: &Redactor,
+    ) -> Result<(), AppError> {
+        let node_version = exec::get_node_version();
+        let ip_address = state
+            .ip
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "unknown".to_owned());
+        let title = debug_info.title();
+
+        let form = reqwest::multipart::Form::new()
+            .part(
+                "payload_json",
+                reqwest::multipart::Part::text(serde_json::to_string(
+                    &serde_json::json!({
+                        "content": redactor.redact(&format!("
+                            node version: {node_version}
+                            address: {ip_address}
+                            network: {network_name}
+                            ETH address: {eth_address:?}
+                        ", network_name = debug_info.network_name(), eth_address = debug_info.address))
+                    }),
+                )?).mime_str("application/json")?,
+            )
+            .part(
+                format!("Logs {title}"),
+                reqwest::multipart::Part::bytes(debug_info.to_tarball()?)
+                    .mime_str("application/gzip")?
+                    .file_name(format!("{title}.tar.gz")),
+            );
+
+        let req = self.client.post(webhook_url).multipart(form).build()?;
+
+        let text = self.client.execute(req).await?.text().await?;
+
+        match serde_json::from_str::<DiscordResponse>(&text) {
+            Ok(DiscordResponse::Success(DiscordSuccessResponse { timestamp })) => {
+                output::note("Send logs", MessageType::LogsReceivedAt { timestamp })?;
+            }
+            Ok(DiscordResponse::Error(DiscordError { code, message })) => {
+                output::note(
+                    "Send logs",
+                    MessageType::LogsSendError {
+                        msg: format!("Code: {code} Message: {message}"),
+                    },
+                )?;
+            }
+            Err(_) => {
+                output::note(
+                    "Send logs",
+                    MessageType::LogsSendError {
+                        msg: format!("Failed to parse: {text}"),
+                    },
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Phase for DebugReportPhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let state = State::read()?;
+            let (debug_info, redactor) = self.collect(&state).await?;
+
+            if let Some(output) = &self.output {
+                tokio::fs::write(output, debug_info.to_tarball()?).await?;
+                output::note(
+                    "Debug report",
+                    MessageType::DebugReportSaved {
+                        path: output.display().to_string(),
+                    },
+                )?;
+                return Ok(());
+            }
+
+            let Some(webhook_url) = &self.discord_webhook_url else {
+                return Err(anyhow!("Debug report destination is not specified").into());
+            };
+
+            output::note("Debug report preview", Self::preview(&debug_info)?)?;
+            if !cliclack::confirm(MessageType::DebugReportAskSend).interact()? {
+                output::log::info(MessageType::DebugReportNotSent)?;
+                return Ok(());
+            }
+
+            self.send_to_discord(webhook_url, &state, &debug_info, &redactor)
+                .await
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,187 @@
+use alloy::{
+    network::AnyNetwork,
+    primitives::{utils::format_ether, U64},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::SyncStatus,
+};
+use futures::stream::{self, StreamExt};
+use futures_util::{future::BoxFuture, FutureExt};
+use std::time::Duration;
+
+use super::{
+    check_status::{CheckStatusPhase, NodeStatus},
+    Phase,
+};
+use crate::{
+    config::Network,
+    error::AppError,
+    messages,
+    utils::{
+        fleet::{
+            self, FleetFormat, FleetHealth, FleetInventory, FleetNode, FleetNodeReport,
+            Registration,
+        },
+        output,
+    },
+};
+use messages::MessageType;
+
+/// Nodes queried at the same time
+const FLEET_CONCURRENCY: usize = 8;
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Queries on-chain status and health of all nodes from the inventory
+pub struct FleetPhase<'a> {
+    inventory: FleetInventory,
+    network: &'a Network,
+    format: FleetFormat,
+}
+
+impl<'a> FleetPhase<'a> {
+    pub fn new(inventory: FleetInventory, network: &'a Network, format: FleetFormat) -> Self {
+        Self {
+            inventory,
+            network,
+            format,
+        }
+    }
+
+    /// Queries node RPC and metrics endpoints, `None` if the inventory has neither
+    async fn health(
+        node: &FleetNode,
+        network_block_number: Option<u64>,
+        errors: &mut Vec<String>,
+    ) -> Option<FleetHealth> {
+        if node.rpc.is_none() && node.metrics.is_none() {
+            return None;
+        }
+
+        let mut health = FleetHealth::default();
+        if let Some(rpc) = &node.rpc {
+            let provider = ProviderBuilder::new()
+                .network::<AnyNetwork>()
+                .on_http(rpc.clone());
+            let query = async {
+                let block_number = provider.get_block_number().await?;
+                let peers_count = provider
+                    .raw_request::<(), U64>("net_peerCount".into(), ())
+                    .await?;
+                let syncing = provider.syncing().await?;
+                Ok::<_, AppError>((block_number, peers_count.to::<u64>(), syncing))
+            };
+
+            match tokio::time::timeout(ENDPOINT_TIMEOUT, query).await {
+                Ok(Ok((block_number, peers_count, syncing))) => {
+                    health.block_number = Some(block_number);
+                    health.block_lag = network_block_number
+                        .map(|network_block| network_block.saturating_sub(block_number));
+                    health.peers_count = Some(peers_count);
+                    health.syncing = Some(matches!(syncing, SyncStatus::Info(_)));
+                }
+                Ok(Err(e)) => errors.push(format!("RPC: {e}")),
+                Err(_) => errors.push("RPC: timeout".to_owned()),
+            }
+        }
+
+        if let Some(metrics) = &node.metrics {
+            let response = reqwest::Client::new()
+                .get(metrics.clone())
+                .timeout(ENDPOINT_TIMEOUT)
+                .send()
+                .await;
+            health.metrics_up = Some(match response {
+                Ok(response) => response.status().is_success(),
+                Err(e) => {
+                    errors.push(format!("Metrics: {e}"));
+                    false
+                }
+            });
+        }
+
+        Some(health)
+    }
+}
+
+impl Phase for FleetPhase<'_> {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let spinner = (self.format == FleetFormat::Table).then(|| {
+                let spinner = output::spinner();
+                spinner.start(MessageType::FleetChecking {
+                    count: self.inventory.nodes.len(),
+                    network: &self.network.name,
+                });
+                spinner
+            });
+
+            let provider_remote = ProviderBuilder::new()
+                .with_recommended_fillers()
+                .network::<AnyNetwork>()
+                .on_http(self.network.rpc.clone());
+            // Lag is unknown if the network is unavailable, node health is checked anyway
+            let network_block_number = provider_remote.get_block_number().await.ok();
+
+            let network = self.network;
+            let reports = stream::iter(&self.inventory.nodes)
+                .map(|node| {
+                    let provider_remote = provider_remote.clone();
+                    async move {
+                        let mut report = FleetNodeReport::new(node);
+
+                        let status = async {
+                            CheckStatusPhase::new(provider_remote, network, node.address)
+                                .await?
+                                .node_status()
+                                .await
+                        };
+                        match status.await {
+                            Ok(NodeStatus::NotRegistered) => {
+                                report.registration = Some(Registration::NotRegistered)
+                            }
+                            Ok(NodeStatus::Onboarding {
+                                stake,
+                                time_to_wait,
+                            }) => {
+                                report.registration = Some(Registration::Onboarding);
+                                report.stake = Some(format_ether(stake));
+                                report.onboarding_wait_secs = Some(time_to_wait.as_secs());
+                            }
+                            Ok(NodeStatus::Onboarded { stake }) => {
+                                report.registration = Some(Registration::Onboarded);
+                                report.stake = Some(format_ether(stake));
+                            }
+                            Err(e) => report.errors.push(format!("Status: {e}")),
+                        }
+
+                        report.health =
+                            Self::health(node, network_block_number, &mut report.errors).await;
+                        report
+                    }
+                })
+                .buffered(FLEET_CONCURRENCY)
+                .collect::<Vec<_>>()
+                .await;
+
+            match self.format {
+                FleetFormat::Table => {
+                    if let Some(spinner) = spinner {
+                        spinner.stop(MessageType::FleetChecked);
+                    }
+                    output::note("Fleet", fleet::render_table(&reports))?;
+                }
+                FleetFormat::Json if output::is_jsonl() => {
+                    output::emit(output::Event::Report {
+                        name: "fleet",
+                        data: serde_json::to_value(&reports)?,
+                    })?;
+                }
+                FleetFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&reports)?);
+                }
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::anyhow;
+use futures_util::{future::BoxFuture, FutureExt};
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    setup::{ImageHistory, Setup, Snapshot, MAX_IMAGE_TAG_LENGTH},
+    state::State,
+    utils::{self, exec, output},
+};
+use messages::MessageType;
+
+/// Number of the latest history entries shown by `version`
+const SHOWN_HISTORY_ENTRIES: usize = 5;
+
+/// Docker image tag format
+static IMAGE_TAG_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9_][A-Za-z0-9_.-]{0,127}$").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageVersionAction {
+    Show,
+    /// Pin image tag or full image reference
+    Pin(String),
+    /// Remove pin and switch to the image from setup templates
+    Upgrade,
+    /// Pin the image used before the current one
+    Rollback,
+}
+
+/// Shows and manages parity image version of the node
+pub struct ImageVersionPhase {
+    action: ImageVersionAction,
+    offline: bool,
+}
+
+impl ImageVersionPhase {
+    pub fn new(action: ImageVersionAction, offline: bool) -> Self {
+        Self { action, offline }
+    }
+
+    fn read_state() -> Result<State, AppError> {
+        let state = State::read()?;
+        if !state.is_complete() {
+            return Err(anyhow!("State is missing some data").into());
+        }
+
+        Ok(state)
+    }
+
+    async fn show() -> Result<(), AppError> {
+        let state = Self::read_state()?;
+        let pinned = state.parity_image.clone();
+        let images = Setup::new(state)?.parity_images().await?;
+        let history = ImageHistory::read().await?;
+
+        let none = MessageType::ImageVersionNone.to_string();
+        let mut lines = vec![
+            MessageType::ImageVersionRunning {
+                image: exec::get_node_version().trim(),
+            }
+            .to_string(),
+            MessageType::ImageVersionConfigured {
+                image: images.current.as_deref().unwrap_or(&none),
+            }
+            .to_string(),
+            MessageType::ImageVersionTemplate {
+                image: &images.template,
+            }
+            .to_string(),
+            MessageType::ImageVersionPinnedImage {
+                image: pinned.as_deref().unwrap_or(&none),
+            }
+            .to_string(),
+        ];
+        if images.current.as_ref() != Some(&images.target) {
+            lines.push(
+                MessageType::ImageVersionPending {
+                    image: &images.target,
+                }
+                .to_string(),
+            );
+        }
+        if !history.entries.is_empty() {
+            lines.push(format!("\n{}", MessageType::ImageVersionHistory));
+            lines.extend(
+                history
+                    .entries
+                    .iter()
+                    .rev()
+                    .take(SHOWN_HISTORY_ENTRIES)
+                    .map(|entry| {
+                        format!(
+                            "  {}  {}",
+                            entry.applied_at.format("%Y-%m-%d %H:%M:%S"),
+                            entry.image
+                        )
+                    }),
+            );
+        }
+
+        output::note("Parity version", lines.join("\n")).map_err(AppError::from)
+    }
+
+    /// Applies the pin to the node and stores it. The previous setup is restored if the image
+    /// can't be pulled or started, so a mistyped tag is never kept
+    async fn apply(&self, parity_image: Option<String>) -> Result<(), AppError> {
+        let mut state = Self::read_state()?;
+        state.parity_image = parity_image;
+
+        let setup = Setup::new(state.clone())?.with_offline(self.offline);
+        let snapshot = Snapshot::take().await?;
+        let result: Result<(), AppError> = async {
+            setup.run().await?;
+
+            output::log::step(MessageType::DockerStarting)?;
+            if !self.offline {
+                exec::run_docker_compose_pull()?;
+            }
+            exec::run_docker_compose_up()
+        }
+        .await;
+
+        if let Err(e) = result {
+            output::log::warning(MessageType::UpdateRollingBack {
+                reason: e.to_string(),
+            })?;
+            let rollback: Result<(), AppError> = async {
+                snapshot.restore().await?;
+                exec::run_docker_compose_up()
+            }
+            .await;
+
+            if let Err(rollback_error) = rollback {
+                return Err(anyhow!(
+                    "{}",
+                    MessageType::UpdateRollbackFailed {
+                        error: e.to_string(),
+                        rollback_error: rollback_error.to_string(),
+                    }
+                )
+                .into());
+            }
+            output::log::success(MessageType::UpdateRolledBack)?;
+
+            return Err(e);
+        }
+
+        state.write().await?;
+
+        let image = setup.parity_images().await?.target;
+        output::log::success(MessageType::ImageVersionApplied { image: &image })
+            .map_err(AppError::from)
+    }
+}
+
+impl Phase for ImageVersionPhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            match &self.action {
+                ImageVersionAction::Show => Self::show().await,
+                ImageVersionAction::Pin(version) => {
+                    let images = Setup::new(Self::read_state()?)?.parity_images().await?;
+                    let image = resolve_image(version, &images.template)?;
+
+                    output::log::info(MessageType::ImageVersionPinned { image: &image })?;
+                    self.apply(Some(image)).await
+                }
+                ImageVersionAction::Upgrade => self.apply(None).await,
+                ImageVersionAction::Rollback => {
+                    let images = Setup::new(Self::read_state()?)?.parity_images().await?;
+                    let current = images.current.unwrap_or(images.target);
+                    let history = ImageHistory::read().await?;
+                    let Some(previous) = history.previous(&current) else {
+                        return Err(anyhow!("{}", MessageType::ImageVersionNoPrevious).into());
+                    };
+
+                    output::log::info(MessageType::ImageVersionRollback {
+                        from: &current,
+                        to: &previous.image,
+                    })?;
+                    self.apply(Some(previous.image.clone())).await
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Turns a bare tag into full image reference using the repository of the template image
+fn resolve_image(version: &str, template_image: &str) -> Result<String, AppError> {
+    if version.contains(':') || version.contains('/') {
+        return match utils::image_tag(version) {
+            Some(tag) if IMAGE_TAG_REGEX.is_match(tag) => {
+                ensure_tag_length(tag).map(|_| version.to_owned())
+            }
+            _ => Err(anyhow!("Image `{version}` has no valid tag").into()),
+        };
+    }
+
+    if !IMAGE_TAG_REGEX.is_match(version) {
+        return Err(anyhow!("`{version}` is not a valid image tag").into());
+    }
+    ensure_tag_length(version)?;
+
+    let repository = template_image
+        .rsplit_once(':')
+        .map_or(template_image, |(repository, _)| repository);
+    Ok(format!("{repository}:{version}"))
+}
+
+/// Tag becomes validator version in parity `extra_data`, which has limited length
+fn ensure_tag_length(tag: &str) -> Result<(), AppError> {
+    if tag.len() > MAX_IMAGE_TAG_LENGTH {
+        return Err(anyhow!(
+            "Image tag `{tag}` is too long, parity allows at most {MAX_IMAGE_TAG_LENGTH} characters"
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_image() {
+        let template = "ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4";
+
+        assert_eq!(
+            resolve_image("v3.3.3-amb1.2.3", template).unwrap(),
+            "ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.3"
+        );
+        assert_eq!(
+            resolve_image("registry.local:5000/openethereum:test", template).unwrap(),
+            "registry.local:5000/openethereum:test"
+        );
+        assert!(resolve_image("registry.local:5000/openethereum", template).is_err());
+        assert!(resolve_image("ghcr.io/ambrosus/openethereum", template).is_err());
+        assert!(resolve_image("-v1", template).is_err());
+        assert!(resolve_image("", template).is_err());
+        assert!(resolve_image(&"a".repeat(MAX_IMAGE_TAG_LENGTH), template).is_ok());
+        assert!(resolve_image(&"a".repeat(MAX_IMAGE_TAG_LENGTH + 1), template).is_err());
+        assert!(resolve_image(&format!("openethereum:{}", "a".repeat(64)), template).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use alloy::{
+    contract::CallBuilder,
+    dyn_abi::JsonAbiExt,
+    network::Network,
+    primitives::Address,
+    providers::{
+        fillers::{FillProvider, TxFiller},
+        Provider,
+    },
+    sol_types::SolValue,
+    transports::Transport,
+};
+use anyhow::anyhow;
+use chrono::Utc;
+use futures_util::{future::BoxFuture, FutureExt};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::Phase;
+use crate::{
+    contract,
+    error::AppError,
+    messages,
+    utils::{
+        maintenance::{self, MaintenanceConfig},
+        output,
+    },
+};
+use messages::MessageType;
+
+/// Checks that a scheduled update runs within the maintenance window and delays it by
+/// the per-node stagger
+pub struct MaintenanceWindowPhase<'a> {
+    config: &'a MaintenanceConfig,
+    address: Address,
+    /// Update must be skipped, since it's outside the window
+    pub skipped: bool,
+}
+
+impl<'a> MaintenanceWindowPhase<'a> {
+    pub fn new(config: &'a MaintenanceConfig, address: Address) -> Self {
+        Self {
+            config,
+            address,
+            skipped: false,
+        }
+    }
+}
+
+impl Phase for MaintenanceWindowPhase<'_> {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let now = Utc::now().time();
+            if let Some(window) = &self.config.window {
+                if !window.contains(now) {
+                    output::log::info(MessageType::MaintenanceOutsideWindow {
+                        start: window.start,
+                        end: window.end,
+                    })?;
+                    self.skipped = true;
+                    return Ok(());
+                }
+            }
+
+            // Restarts are spread over the rest of the window at most, so they don't land after its end
+            let mut max_delay = Duration::from_secs(self.config.stagger_secs);
+            if let Some(window) = &self.config.window {
+                max_delay = max_delay.min(window.remaining(now));
+            }
+            let delay = maintenance::stagger_delay(&self.address, max_delay);
+            if !delay.is_zero() {
+                output::log::info(MessageType::MaintenanceStaggerDelay { delay })?;
+                tokio::time::sleep(delay).await;
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Waits until the node has authored its upcoming block if it's due sooner than the margin,
+/// so the restart doesn't make the validator miss its step
+pub struct AuthorSlotPhase<
+    F,
+    P: Provider<T, N> + Send + Sync + Clone,
+    T: Transport + Clone,
+    N: Network + Clone,
+> where
+    F: TxFiller<N>,
+{
+    provider: FillProvider<F, P, T, N>,
+    address: Address,
+    step_duration: u64,
+    margin: Duration,
+}
+
+impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone>
+    AuthorSlotPhase<F, P, T, N>
+where
+    F: TxFiller<N>,
+{
+    pub fn new(
+        provider: FillProvider<F, P, T, N>,
+        address: Address,
+        step_duration: u64,
+        margin: Duration,
+    ) -> Self {
+        Self {
+            provider,
+            address,
+            step_duration,
+            margin,
+        }
+    }
+
+    async fn get_validators(&self) -> Result<Vec<Address>, AppError> {
+        let contracts = contract::deployment(self.provider.get_chain_id().await?)?;
+        let validator_set = contracts
+            .get("ValidatorSet")
+            .ok_or_else(|| anyhow!("Unable to find contract `ValidatorSet` abi"))?;
+        let input = validator_set
+            .function("getValidators")?
+            .abi_encode_input(&[])?;
+
+        let output = CallBuilder::new_raw(&self.provider, input.into())
+            .to(validator_set.address)
+            .call()
+            .await?;
+
+        Vec::<Address>::abi_decode(&output.0, true).map_err(AppError::from)
+    }
+}
+
+impl<F, P: Provider<T, N> + Send + Sync + Clone, T: Transport + Clone, N: Network + Clone> Phase
+    for AuthorSlotPhase<F, P, T, N>
+where
+    F: TxFiller<N>,
+{
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let validators = match self.get_validators().await {
+                Ok(validators) => validators,
+                // Restart protection is best effort, the update itself must go on
+                Err(e) => {
+                    output::log::warning(MessageType::MaintenanceAuthorCheckFailed {
+                        reason: e.to_string(),
+                    })?;
+                    return Ok(());
+                }
+            };
+
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let Some(next_step_in) = maintenance::next_author_step_in(
+                &validators,
+                &self.address,
+                self.step_duration,
+                now,
+            ) else {
+                return Ok(());
+            };
+
+            if next_step_in < self.margin {
+                // Waits for the end of the step, when the block has been sealed
+                let delay = next_step_in + Duration::from_secs(self.step_duration);
+                output::log::info(MessageType::MaintenanceAuthorWait { delay })?;
+                tokio::time::sleep(delay).await;
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -1,9 +1,21 @@
 pub mod actions_menu;
 pub mod check_docker;
+pub mod check_health;
+pub mod check_host;
+pub mod check_ip;
+pub mod check_p2p;
 pub mod check_status;
+pub mod debug_report;
+pub mod fleet;
+pub mod image_version;
+pub mod maintenance;
+pub mod nodes;
+pub mod pipeline;
 pub mod select_network;
 pub mod select_node_ip;
 pub mod select_private_key;
+pub mod self_update;
+pub mod view_logs;
 
 use futures_util::future::BoxFuture;
 
@@ -0,0 +1,137 @@
+use alloy::{
+    network::AnyNetwork,
+    providers::{Provider, ProviderBuilder},
+    rpc::types::SyncStatus,
+};
+use futures_util::{future::BoxFuture, FutureExt};
+use std::time::Duration;
+
+use super::{
+    check_health::{ETHSTATS_CONTAINER_NAME, PARITY_CONTAINER_NAME},
+    Phase,
+};
+use crate::{
+    error::AppError,
+    messages,
+    state::State,
+    utils::{
+        exec, output,
+        profile::{self, Profile},
+    },
+};
+use messages::MessageType;
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodesAction {
+    /// Profiles with their network, address and ports
+    List,
+    /// Containers and sync state of every profile
+    Status,
+}
+
+/// Summarizes all node profiles configured on this host
+pub struct NodesPhase {
+    action: NodesAction,
+}
+
+impl NodesPhase {
+    pub fn new(action: NodesAction) -> Self {
+        Self { action }
+    }
+
+    /// Profile name, the active one is marked with `*`
+    fn title(profile: &Profile) -> String {
+        let mark = if profile == profile::active() {
+            "*"
+        } else {
+            " "
+        };
+        format!("{mark} {}", profile.name())
+    }
+
+    fn describe(profile: &Profile, state: &State) -> String {
+        let ports = state.ports();
+        format!(
+            "{:<16} {:<8} {:<42} {:<16} {:<20} {}",
+            Self::title(profile),
+            state
+                .network
+                .as_ref()
+                .map(|network| network.name.as_str())
+                .unwrap_or("-"),
+            state
+                .address
+                .map(|address| address.to_string())
+                .unwrap_or_else(|| "-".to_owned()),
+            state
+                .ip
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|| "-".to_owned()),
+            MessageType::NodesPorts {
+                p2p: ports.p2p,
+                rpc: ports.rpc,
+            }
+            .to_string(),
+            profile.output_dir().display(),
+        )
+    }
+
+    async fn status(profile: &Profile, state: &State) -> String {
+        let container_status = |name: &str| {
+            exec::get_container_status(&profile.container_name(name))
+                .unwrap_or_else(|| MessageType::StatusNotFound.to_string())
+        };
+
+        let sync = match profile.local_rpc_url(&state.ports()) {
+            Ok(url) => {
+                let provider = ProviderBuilder::new().network::<AnyNetwork>().on_http(url);
+                let query = async {
+                    let block_number = provider.get_block_number().await?;
+                    let syncing = provider.syncing().await?;
+                    Ok::<_, AppError>((block_number, syncing))
+                };
+
+                match tokio::time::timeout(RPC_TIMEOUT, query).await {
+                    Ok(Ok((block, SyncStatus::None))) => MessageType::NodesSynced { block },
+                    Ok(Ok((block, SyncStatus::Info(_)))) => MessageType::NodesSyncing { block },
+                    Ok(Err(e)) => MessageType::NodesRpcError { msg: e.to_string() },
+                    Err(_) => MessageType::NodesRpcTimeout,
+                }
+            }
+            Err(e) => MessageType::NodesRpcError { msg: e.to_string() },
+        };
+
+        format!(
+            "{:<16} parity: {:<10} ethstats: {:<10} {sync}",
+            Self::title(profile),
+            container_status(PARITY_CONTAINER_NAME),
+            container_status(ETHSTATS_CONTAINER_NAME),
+        )
+    }
+}
+
+impl Phase for NodesPhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let profiles = profile::list()?;
+            if profiles.is_empty() {
+                output::log::info(MessageType::NodesNotConfigured)?;
+                return Ok(());
+            }
+
+            let mut lines = Vec::with_capacity(profiles.len());
+            for profile in &profiles {
+                let state = profile.state()?;
+                lines.push(match self.action {
+                    NodesAction::List => Self::describe(profile, &state),
+                    NodesAction::Status => Self::status(profile, &state).await,
+                });
+            }
+
+            output::note("Nodes", lines.join("\n")).map_err(AppError::from)
+        }
+        .boxed()
+    }
+}
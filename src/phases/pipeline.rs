@@ -0,0 +1,658 @@
+use alloy::{
+    network::AnyNetwork,
+    providers::{Provider, ProviderBuilder},
+};
+use anyhow::anyhow;
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use std::net::IpAddr;
+
+use super::{
+    check_docker::DockerAvailablePhase,
+    check_health::HealthCheckPhase,
+    check_host::HostRequirementsPhase,
+    check_status::CheckStatusPhase,
+    select_network::{self, SelectNetworkPhase},
+    select_node_ip::{self, SelectNodeIP},
+    select_private_key::{self, SelectPrivateKeyPhase},
+    Phase,
+};
+use crate::{
+    config::{Config, Network},
+    error::AppError,
+    messages::MessageType,
+    setup::Setup,
+    state::{RunProgress, State},
+    utils::{self, output, profile},
+};
+
+/// Inputs of the setup run passed with command line options
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub offline: bool,
+    pub network: Option<String>,
+    pub ip: Option<IpAddr>,
+    pub generate_key: bool,
+}
+
+/// Data shared by the pipeline phases, results of the phases go into the state
+pub struct RunContext<'a> {
+    pub config: &'a Config,
+    pub options: RunOptions,
+    pub state: State,
+}
+
+impl RunContext<'_> {
+    /// Network passed with `--network`
+    fn network_option(&self) -> Result<Option<&Network>, AppError> {
+        self.options
+            .network
+            .as_ref()
+            .map(|name| {
+                self.config
+                    .networks
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Unknown network `{name}`").into())
+            })
+            .transpose()
+    }
+
+    fn network(&self) -> Result<&Network, AppError> {
+        self.state
+            .network
+            .as_ref()
+            .ok_or_else(|| anyhow!("No network selected").into())
+    }
+}
+
+/// Step of the setup run with metadata used to skip, resume and plan it
+pub trait PipelinePhase {
+    /// Unique name used in events, progress and dependencies
+    fn name(&self) -> &'static str;
+
+    /// Phases whose results this one uses, it runs again if any of them does
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether the state already has the result of the phase, so it doesn't have to run.
+    /// `None` if the state doesn't keep it, then progress of the previous run is used
+    fn is_satisfied(&self, _context: &RunContext) -> Option<bool> {
+        None
+    }
+
+    /// Shows the result kept in the state when the phase is skipped as satisfied, so the
+    /// operator can still confirm it
+    fn report_satisfied(&self, _context: &RunContext) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// Phase asks the operator unless the input is passed with an option
+    fn is_interactive(&self, _context: &RunContext) -> bool {
+        false
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhaseAction {
+    Run,
+    /// Skipped, the state already has the result
+    Satisfied,
+    /// Skipped, completed by the previous run which has failed or been interrupted
+    Completed,
+}
+
+impl PhaseAction {
+    fn resolve(
+        phase: &dyn PipelinePhase,
+        context: &RunContext,
+        previous: &RunProgress,
+        running: &[&str],
+    ) -> Self {
+        match phase.is_satisfied(context) {
+            Some(true) => Self::Satisfied,
+            Some(false) => Self::Run,
+            None if previous.completed.iter().any(|name| name == phase.name())
+                && !phase
+                    .dependencies()
+                    .iter()
+                    .any(|dependency| running.contains(dependency)) =>
+            {
+                Self::Completed
+            }
+            None => Self::Run,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Run => "run",
+            Self::Satisfied => "satisfied",
+            Self::Completed => "completed",
+        }
+    }
+}
+
+/// Ordered phases of the setup run
+pub struct Pipeline {
+    phases: Vec<Box<dyn PipelinePhase>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        let phases: Vec<Box<dyn PipelinePhase>> = vec![
+            Box::new(DockerAvailable),
+            Box::new(HostRequirements),
+            Box::new(NetworkSelection),
+            Box::new(PrivateKeySelection),
+            Box::new(NodeIpSelection),
+            Box::new(SetupFiles),
+            Box::new(DockerStart),
+            Box::new(HealthCheck),
+            Box::new(StatusCheck),
+        ];
+        Self::new(phases)
+    }
+}
+
+impl Pipeline {
+    pub fn new(phases: Vec<Box<dyn PipelinePhase>>) -> Self {
+        for (index, phase) in phases.iter().enumerate() {
+            for dependency in phase.dependencies() {
+                assert!(
+                    phases[..index]
+                        .iter()
+                        .any(|previous| previous.name() == *dependency),
+                    "`{}` depends on `{dependency}`, which doesn't precede it",
+                    phase.name()
+                );
+            }
+        }
+
+        Self { phases }
+    }
+
+    /// Actions the next run takes, doesn't change anything
+    pub fn plan(&self, context: &RunContext) -> Vec<(&dyn PipelinePhase, PhaseAction)> {
+        let previous = context.state.progress.clone().unwrap_or_default();
+        let mut running = Vec::new();
+
+        self.phases
+            .iter()
+            .map(|phase| {
+                let action = PhaseAction::resolve(phase.as_ref(), context, &previous, &running);
+                if action == PhaseAction::Run {
+                    running.push(phase.name());
+                }
+                (phase.as_ref(), action)
+            })
+            .collect()
+    }
+
+    /// Reports the plan as a note or as events in JSON lines mode
+    pub fn print_plan(&self, context: &RunContext) -> Result<(), AppError> {
+        let plan = self.plan(context);
+        if output::is_jsonl() {
+            for (phase, action) in plan {
+                output::emit(output::Event::PhasePlanned {
+                    phase: phase.name(),
+                    action: action.as_str(),
+                    interactive: phase.is_interactive(context),
+                    dependencies: phase.dependencies(),
+                })?;
+            }
+            return Ok(());
+        }
+
+        let lines = plan
+            .iter()
+            .enumerate()
+            .map(|(index, (phase, action))| {
+                let mut details = Vec::new();
+                if phase.is_interactive(context) {
+                    details.push("interactive".to_owned());
+                }
+                if !phase.dependencies().is_empty() {
+                    details.push(format!("after {}", phase.dependencies().join(", ")));
+                }
+                format!(
+                    "{:>2}. {:<24} {:<10} {}",
+                    index + 1,
+                    phase.name(),
+                    action.as_str(),
+                    details.join("; ")
+                )
+            })
+            .collect::<Vec<_>>();
+        output::note("Setup plan", lines.join("\n"))?;
+
+        Ok(())
+    }
+
+    /// Runs the phases which aren't satisfied or completed yet. Progress is stored in the state
+    /// after every phase, so the next run resumes at the failed one
+    pub async fn run(&self, context: &mut RunContext<'_>) -> Result<(), AppError> {
+        let previous = context.state.progress.take().unwrap_or_default();
+        let mut progress = RunProgress::default();
+        let mut running = Vec::new();
+
+        for phase in &self.phases {
+            let name = phase.name();
+            match PhaseAction::resolve(phase.as_ref(), context, &previous, &running) {
+                PhaseAction::Satisfied => {
+                    output::skip(
+                        name,
+                        PhaseAction::Satisfied.as_str(),
+                        MessageType::PipelinePhaseSatisfied { phase: name },
+                    )?;
+                    phase.report_satisfied(context)?;
+                }
+                PhaseAction::Completed => {
+                    output::skip(
+                        name,
+                        PhaseAction::Completed.as_str(),
+                        MessageType::PipelinePhaseCompleted { phase: name },
+                    )?;
+                }
+                PhaseAction::Run => {
+                    running.push(name);
+                    if let Err(e) = output::track(name, phase.run(context)).await {
+                        progress.failed = Some(name.to_owned());
+                        context.state.progress = Some(progress);
+                        context.state.write().await?;
+                        return Err(e);
+                    }
+                }
+            }
+
+            progress.completed.push(name.to_owned());
+            context.state.progress = Some(progress.clone());
+            context.state.write().await?;
+        }
+
+        context.state.progress = None;
+        context.state.write().await
+    }
+}
+
+struct DockerAvailable;
+
+impl PipelinePhase for DockerAvailable {
+    fn name(&self) -> &'static str {
+        "docker_available"
+    }
+
+    fn run<'a>(
+        &'a self,
+        _context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move { DockerAvailablePhase {}.run().await }.boxed_local()
+    }
+}
+
+struct HostRequirements;
+
+impl PipelinePhase for HostRequirements {
+    fn name(&self) -> &'static str {
+        "host_requirements"
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            HostRequirementsPhase::new(context.state.ports())
+                .run()
+                .await
+        }
+        .boxed_local()
+    }
+}
+
+struct NetworkSelection;
+
+impl PipelinePhase for NetworkSelection {
+    fn name(&self) -> &'static str {
+        "network_selection"
+    }
+
+    fn is_satisfied(&self, context: &RunContext) -> Option<bool> {
+        Some(
+            context.options.network.is_none()
+                && context.state.network.as_ref().is_some_and(|network| {
+                    context.config.networks.get(&network.name) == Some(network)
+                }),
+        )
+    }
+
+    fn report_satisfied(&self, context: &RunContext) -> Result<(), AppError> {
+        select_network::report(context.network()?).map_err(AppError::from)
+    }
+
+    fn is_interactive(&self, context: &RunContext) -> bool {
+        self.is_satisfied(context) != Some(true)
+            && context.options.network.is_none()
+            && context.config.networks.len() > 1
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            let network = context.network_option()?;
+            let mut select_network = SelectNetworkPhase::new(
+                network.or(context.state.network.as_ref()),
+                &context.config.networks,
+            );
+            select_network.run().await?;
+            context.state.network = select_network.network.cloned();
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
+struct PrivateKeySelection;
+
+impl PipelinePhase for PrivateKeySelection {
+    fn name(&self) -> &'static str {
+        "private_key_selection"
+    }
+
+    fn is_satisfied(&self, context: &RunContext) -> Option<bool> {
+        Some(context.state.private_key.is_some())
+    }
+
+    fn report_satisfied(&self, context: &RunContext) -> Result<(), AppError> {
+        match &context.state.private_key {
+            Some(private_key) => select_private_key::report(private_key).map_err(AppError::from),
+            None => Ok(()),
+        }
+    }
+
+    fn is_interactive(&self, context: &RunContext) -> bool {
+        context.state.private_key.is_none() && !context.options.generate_key
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            let mut select_private_key =
+                SelectPrivateKeyPhase::new(context.state.private_key.clone())
+                    .with_generate(context.options.generate_key);
+            select_private_key.run().await?;
+            context.state.address = select_private_key
+                .private_key
+                .as_ref()
+                .map(utils::secp256k1_signing_key_to_eth_address);
+            context.state.private_key = select_private_key.private_key;
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
+struct NodeIpSelection;
+
+impl PipelinePhase for NodeIpSelection {
+    fn name(&self) -> &'static str {
+        "node_ip_selection"
+    }
+
+    fn is_satisfied(&self, context: &RunContext) -> Option<bool> {
+        // IPv6 address left from previous versions is rejected by `SelectNodeIP`
+        Some(
+            context.state.ip.is_some_and(|ip| ip.is_ipv4())
+                && context
+                    .options
+                    .ip
+                    .map_or(true, |ip| context.state.ip == Some(ip)),
+        )
+    }
+
+    fn report_satisfied(&self, context: &RunContext) -> Result<(), AppError> {
+        match &context.state.ip {
+            Some(ip) => select_node_ip::report(ip).map_err(AppError::from),
+            None => Ok(()),
+        }
+    }
+
+    fn is_interactive(&self, context: &RunContext) -> bool {
+        context.state.ip.is_none() && context.options.ip.is_none()
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            let mut select_node_ip = SelectNodeIP::new(context.options.ip.or(context.state.ip));
+            select_node_ip.run().await?;
+            context.state.ip = select_node_ip.node_ip;
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
+struct SetupFiles;
+
+impl PipelinePhase for SetupFiles {
+    fn name(&self) -> &'static str {
+        "setup"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[
+            "network_selection",
+            "private_key_selection",
+            "node_ip_selection",
+        ]
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            Setup::new(context.state.clone())?
+                .with_offline(context.options.offline)
+                .run()
+                .await
+        }
+        .boxed_local()
+    }
+}
+
+struct DockerStart;
+
+impl PipelinePhase for DockerStart {
+    fn name(&self) -> &'static str {
+        "docker_start"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["setup"]
+    }
+
+    fn run<'a>(
+        &'a self,
+        _context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            output::log::step(MessageType::DockerStarting)?;
+            utils::exec::run_docker_compose_up()
+        }
+        .boxed_local()
+    }
+}
+
+struct HealthCheck;
+
+impl PipelinePhase for HealthCheck {
+    fn name(&self) -> &'static str {
+        "health_check"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["docker_start"]
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            let provider_remote = ProviderBuilder::new()
+                .with_recommended_fillers()
+                .network::<AnyNetwork>()
+                .on_http(context.network()?.rpc.clone());
+            let provider_local = ProviderBuilder::new()
+                .with_recommended_fillers()
+                .network::<AnyNetwork>()
+                .on_http(profile::active().local_rpc_url(&context.state.ports())?);
+
+            let chain_id =
+                match Setup::known_chain_id(context.network()?, context.options.offline).await? {
+                    Some(chain_id) => chain_id,
+                    None => provider_remote.get_chain_id().await?,
+                };
+
+            let mut check_health = HealthCheckPhase::new(provider_local, chain_id);
+            check_health.run().await?;
+            if check_health.report.is_healthy() {
+                output::log::step(MessageType::DockerStarted)?;
+            }
+
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
+struct StatusCheck;
+
+impl PipelinePhase for StatusCheck {
+    fn name(&self) -> &'static str {
+        "status_check"
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["network_selection", "private_key_selection"]
+    }
+
+    fn run<'a>(
+        &'a self,
+        context: &'a mut RunContext<'_>,
+    ) -> LocalBoxFuture<'a, Result<(), AppError>> {
+        async move {
+            let network = context.network()?;
+            let address = context
+                .state
+                .address
+                .ok_or_else(|| anyhow!("No private key specified"))?;
+            let provider_remote = ProviderBuilder::new()
+                .with_recommended_fillers()
+                .network::<AnyNetwork>()
+                .on_http(network.rpc.clone());
+
+            CheckStatusPhase::new(provider_remote, network, address)
+                .await?
+                .run()
+                .await
+        }
+        .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> Config {
+        serde_json::from_value(json!({
+            "discordWebhookUrl": "",
+            "networks": {
+                "test": {
+                    "domain": "test.airdao.io",
+                    "rpc": "https://network.test.airdao.io",
+                    "chainspec": "https://chainspec.test.airdao.io/test.json",
+                    "explorerUrl": "https://test.airdao.io",
+                    "name": "test",
+                },
+            },
+            "releaseFeedUrl": "https://example.com/releases/latest",
+        }))
+        .unwrap()
+    }
+
+    fn actions(pipeline: &Pipeline, context: &RunContext) -> Vec<PhaseAction> {
+        pipeline
+            .plan(context)
+            .into_iter()
+            .map(|(_, action)| action)
+            .collect()
+    }
+
+    #[test]
+    fn test_pipeline_plan() {
+        use PhaseAction::*;
+
+        let config = config();
+        let pipeline = Pipeline::default();
+
+        let mut context = RunContext {
+            config: &config,
+            options: RunOptions::default(),
+            state: State::default(),
+        };
+        assert_eq!(actions(&pipeline, &context), [Run; 9]);
+        let interactive = pipeline
+            .plan(&context)
+            .into_iter()
+            .filter(|(phase, _)| phase.is_interactive(&context))
+            .map(|(phase, _)| phase.name())
+            .collect::<Vec<_>>();
+        assert_eq!(interactive, ["private_key_selection", "node_ip_selection"]);
+
+        // Resumes at the failed phase
+        context.state.network = config.networks.get("test").cloned();
+        context.state.private_key = Some(k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap());
+        context.state.ip = Some("203.0.113.7".parse().unwrap());
+        context.state.progress = Some(RunProgress {
+            completed: vec![
+                "docker_available".to_owned(),
+                "host_requirements".to_owned(),
+                "network_selection".to_owned(),
+                "private_key_selection".to_owned(),
+                "node_ip_selection".to_owned(),
+                "setup".to_owned(),
+                "docker_start".to_owned(),
+            ],
+            failed: Some("health_check".to_owned()),
+        });
+        assert_eq!(
+            actions(&pipeline, &context),
+            [
+                Completed, Completed, Satisfied, Satisfied, Satisfied, Completed, Completed, Run,
+                Run
+            ]
+        );
+
+        // Phases run again if their dependencies do
+        context.options.ip = Some("203.0.113.8".parse().unwrap());
+        assert_eq!(
+            actions(&pipeline, &context),
+            [Completed, Completed, Satisfied, Satisfied, Run, Run, Run, Run, Run]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use futures_util::{future::BoxFuture, FutureExt};
 use std::collections::HashMap;
 
 use super::Phase;
-use crate::{config::Network, error, messages};
+use crate::{config::Network, error, messages, utils::output};
 use messages::MessageType;
 
 pub struct SelectNetworkPhase<'a> {
@@ -44,6 +44,7 @@ impl Phase for SelectNetworkPhase<'_> {
                 let selected = if self.available_networks.len() == 1 {
                     initial_network
                 } else {
+                    output::require_interactive("--network")?;
                     cliclack::select(MessageType::NetworkRequest)
                         .items(
                             &self
@@ -60,12 +61,7 @@ impl Phase for SelectNetworkPhase<'_> {
             }
 
             if let Some(network) = self.network {
-                cliclack::note(
-                    "Network check",
-                    MessageType::NetworkSelected {
-                        network: &network.name,
-                    },
-                )?;
+                report(network)?;
                 Ok(())
             } else {
                 Err(anyhow!("No network selected").into())
@@ -74,3 +70,13 @@ impl Phase for SelectNetworkPhase<'_> {
         .boxed()
     }
 }
+
+/// Shows the network the node runs in
+pub fn report(network: &Network) -> std::io::Result<()> {
+    output::note(
+        "Network check",
+        MessageType::NetworkSelected {
+            network: &network.name,
+        },
+    )
+}
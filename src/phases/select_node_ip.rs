@@ -7,6 +7,7 @@ use super::Phase;
 use crate::{
     error::{self, AppError},
     messages,
+    utils::{net, output},
 };
 use messages::MessageType;
 
@@ -20,25 +21,87 @@ impl SelectNodeIP {
     }
 }
 
+impl SelectNodeIP {
+    async fn ask_node_ip() -> Result<IpAddr, AppError> {
+        output::require_interactive("--ip")?;
+        let discovery = net::discover_public_ips().await?;
+        let public_ips = &discovery.candidates;
+        if public_ips.len() > 1 {
+            output::log::warning(MessageType::NodeIpSourcesDisagree {
+                ips: public_ips
+                    .iter()
+                    .map(|candidate| format!("{} ({})", candidate.ip, candidate.sources.join(", ")))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })?;
+        }
+
+        // `None` stands for manual input
+        let mut items = public_ips
+            .iter()
+            .map(|candidate| {
+                (
+                    Some(candidate.ip),
+                    candidate.ip.to_string(),
+                    MessageType::NodeIpPublicLabel {
+                        confirmed: candidate.sources.len(),
+                        total: discovery.sources_count,
+                    }
+                    .to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for address in net::local_interface_addresses().unwrap_or_default() {
+            let ip = IpAddr::V4(address.ip);
+            if !discovery.contains(&ip) {
+                items.push((
+                    Some(ip),
+                    address.ip.to_string(),
+                    if net::is_global_address(&address.ip) {
+                        MessageType::NodeIpInterfaceLabel {
+                            interface: &address.interface,
+                        }
+                    } else {
+                        MessageType::NodeIpPrivateInterfaceLabel {
+                            interface: &address.interface,
+                        }
+                    }
+                    .to_string(),
+                ));
+            }
+        }
+        items.push((
+            None,
+            MessageType::NodeIpInputManually.to_string(),
+            String::new(),
+        ));
+
+        let selected = cliclack::select(MessageType::NodeIpSelectRequest)
+            .items(&items)
+            .initial_value(public_ips.first().map(|candidate| candidate.ip))
+            .interact()?;
+        if let Some(ip) = selected {
+            return Ok(ip);
+        }
+
+        let ip_text: String = cliclack::input(MessageType::NodeIpInputManually)
+            .validate_interactively(|input: &String| validate_ip_input(input, true))
+            .validate(|input: &String| validate_ip_input(input, false))
+            .interact()?;
+        IpAddr::from_str(&ip_text).map_err(AppError::from)
+    }
+}
+
 impl Phase for SelectNodeIP {
     fn run(&mut self) -> BoxFuture<'_, Result<(), error::AppError>> {
         async {
             if self.node_ip.is_none() {
-                let my_ip = fetch_my_ip().await?;
-
-                if cliclack::confirm(MessageType::NodeIpConfirmRequest { ip: my_ip }).interact()? {
-                    self.node_ip = Some(my_ip);
-                } else {
-                    let ip_text: String = cliclack::input(MessageType::NodeIpInputManually)
-                        .validate_interactively(|input: &String| validate_ip_input(input, true))
-                        .validate(|input: &String| validate_ip_input(input, false))
-                        .interact()?;
-                    self.node_ip = Some(IpAddr::from_str(&ip_text)?);
-                }
+                self.node_ip = Some(Self::ask_node_ip().await?);
             }
 
             if let Some(ip) = &self.node_ip {
-                cliclack::note("Node IP check", MessageType::NodeIpInfo { ip })?;
+                ensure_supported_ip(ip)?;
+                report(ip)?;
                 Ok(())
             } else {
                 Err(anyhow!("No IP specified for node").into())
@@ -48,10 +111,9 @@ impl Phase for SelectNodeIP {
     }
 }
 
-async fn fetch_my_ip() -> Result<IpAddr, AppError> {
-    let res = reqwest::get("https://api.ipify.org/").await?;
-    let text = res.text().await?;
-    IpAddr::from_str(&text).map_err(AppError::from)
+/// Shows the IP the node advertises to peers
+pub fn report(ip: &IpAddr) -> std::io::Result<()> {
+    output::note("Node IP check", MessageType::NodeIpInfo { ip })
 }
 
 fn validate_ip_input(input: &str, interactive: bool) -> Result<(), AppError> {
@@ -78,6 +140,16 @@ fn validate_ip_input(input: &str, interactive: bool) -> Result<(), AppError> {
     }
 }
 
+/// Node IP may also come from `--ip` or an existing state. Parity takes the host part of `extip`
+/// up to the first `:`, so only IPv4 address can be advertised
+pub fn ensure_supported_ip(ip: &IpAddr) -> Result<(), AppError> {
+    if ip.is_ipv6() {
+        return Err(anyhow!("{}", MessageType::NodeIpv6Unsupported { ip }).into());
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,17 +6,27 @@ use rand::rngs::OsRng;
 use super::Phase;
 use crate::{
     error::{self, AppError},
-    messages, utils,
+    messages,
+    utils::{self, output},
 };
 use messages::MessageType;
 
 pub struct SelectPrivateKeyPhase {
     pub private_key: Option<SigningKey>,
+    generate: bool,
 }
 
 impl SelectPrivateKeyPhase {
     pub fn new(private_key: Option<SigningKey>) -> Self {
-        Self { private_key }
+        Self {
+            private_key,
+            generate: false,
+        }
+    }
+
+    /// Generates a new key instead of asking the operator if there is none yet
+    pub fn with_generate(self, generate: bool) -> Self {
+        Self { generate, ..self }
     }
 }
 
@@ -29,7 +39,12 @@ enum PrivateKeyInputKind {
 impl Phase for SelectPrivateKeyPhase {
     fn run(&mut self) -> BoxFuture<'_, Result<(), error::AppError>> {
         async {
+            if self.private_key.is_none() && self.generate {
+                self.private_key = Some(SigningKey::random(&mut OsRng));
+            }
+
             if self.private_key.is_none() {
+                output::require_interactive("--generate-key")?;
                 match cliclack::select(MessageType::NoPrivateKey)
                     .items(&[
                         (
@@ -64,12 +79,7 @@ impl Phase for SelectPrivateKeyPhase {
             }
 
             if let Some(private_key) = &self.private_key {
-                cliclack::note(
-                    "Private key check",
-                    MessageType::PrivateKeyVerified {
-                        address: utils::secp256k1_signing_key_to_eth_address(private_key),
-                    },
-                )?;
+                report(private_key)?;
                 Ok(())
             } else {
                 Err(anyhow!("No private key specified").into())
@@ -79,6 +89,16 @@ impl Phase for SelectPrivateKeyPhase {
     }
 }
 
+/// Shows the address of the key the node signs blocks with
+pub fn report(private_key: &SigningKey) -> std::io::Result<()> {
+    output::note(
+        "Private key check",
+        MessageType::PrivateKeyVerified {
+            address: utils::secp256k1_signing_key_to_eth_address(private_key),
+        },
+    )
+}
+
 fn validate_private_key_input(input: &str, interactive: bool) -> Result<(), AppError> {
     let input = utils::skip_hex_prefix(input);
 
@@ -0,0 +1,77 @@
+use anyhow::anyhow;
+use futures_util::{future::BoxFuture, FutureExt};
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    utils::{output, release, sysinfo},
+};
+use messages::MessageType;
+
+/// Installs the latest NOP release, its config and setup templates in place of the running ones
+pub struct SelfUpdatePhase {
+    release_feed_url: reqwest::Url,
+    /// Reinstall even if the latest release is already running
+    force: bool,
+    /// New binary has been installed, NOP must be restarted to use it
+    pub updated: bool,
+}
+
+impl SelfUpdatePhase {
+    pub fn new(release_feed_url: reqwest::Url, force: bool) -> Self {
+        Self {
+            release_feed_url,
+            force,
+            updated: false,
+        }
+    }
+}
+
+impl Phase for SelfUpdatePhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let release = release::fetch_latest(&self.release_feed_url).await?;
+            let current = release::current_version();
+            let latest = release.version()?;
+
+            // Never downgrades, the latest release may lag behind a locally built binary
+            if latest <= current && !self.force {
+                output::log::info(MessageType::SelfUpdateUpToDate { version: &current })?;
+                return Ok(());
+            }
+            output::log::step(MessageType::SelfUpdateStepDownloading {
+                current: &current,
+                latest: &latest,
+            })?;
+
+            let artifact_name =
+                release::artifact_name(&sysinfo::os_release()?, std::env::consts::ARCH)?;
+            let Some(asset) = release.asset(artifact_name) else {
+                return Err(anyhow!(
+                    "Release {} has no `{artifact_name}` artifact",
+                    release.tag_name
+                )
+                .into());
+            };
+            let archive = release::download(&asset.browser_download_url).await?;
+            let bundle = release::extract_bundle(&archive)?;
+
+            // Key installed with the current binary is trusted, the one shipped in the archive is not
+            let binary_path = std::env::current_exe()?;
+            let public_key_path = binary_path.with_file_name(release::PUBLIC_KEY_FILE_NAME);
+            let public_key = tokio::fs::read_to_string(&public_key_path)
+                .await
+                .map_err(|e| anyhow!("Release public key {public_key_path:?} not found: {e}"))?;
+            release::verify_signature(&bundle.binary, &bundle.signature, &public_key)?;
+            output::log::success(MessageType::SelfUpdateSignatureVerified)?;
+
+            release::install_bundle(&binary_path, &bundle, &latest.to_string()).await?;
+            self.updated = true;
+
+            output::log::success(MessageType::SelfUpdateInstalled { version: &latest })
+                .map_err(AppError::from)
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::anyhow;
+use futures_util::{future::BoxFuture, FutureExt};
+use regex::Regex;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+use super::Phase;
+use crate::{
+    error::AppError,
+    messages,
+    utils::{
+        exec,
+        logs::{
+            ImportStallDetector, LogFilter, LogLevel, LogLine, ETHSTATS_SERVICE_NAME,
+            PARITY_SERVICE_NAME,
+        },
+        output,
+    },
+};
+use messages::MessageType;
+
+const DEFAULT_TAIL_LINES: usize = 200;
+/// How often block import stall is checked while following logs
+const IMPORT_STALL_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct ViewLogsPhase {
+    filter: Option<LogFilter>,
+}
+
+impl ViewLogsPhase {
+    /// Creates a phase that streams logs with the given filter, or asks the operator for one if `None`
+    pub fn new(filter: Option<LogFilter>) -> Self {
+        Self { filter }
+    }
+
+    fn ask_filter() -> Result<LogFilter, AppError> {
+        let services = cliclack::multiselect(MessageType::LogsSelectServices)
+            .items(&[
+                (PARITY_SERVICE_NAME, PARITY_SERVICE_NAME, ""),
+                (ETHSTATS_SERVICE_NAME, ETHSTATS_SERVICE_NAME, ""),
+            ])
+            .initial_values(vec![PARITY_SERVICE_NAME])
+            .interact()?;
+
+        let level = cliclack::select(MessageType::LogsSelectLevel)
+            .items(&[
+                (None, "all", ""),
+                (Some(LogLevel::Info), "info", ""),
+                (Some(LogLevel::Warn), "warn", ""),
+                (Some(LogLevel::Error), "error", ""),
+            ])
+            .initial_value(None)
+            .interact()?;
+
+        let search: String = cliclack::input(MessageType::LogsSearchRequest)
+            .required(false)
+            .validate(|input: &String| {
+                Regex::new(input)
+                    .map(|_| ())
+                    .map_err(|e| MessageType::LogsInvalidSearch { msg: e.to_string() })
+            })
+            .interact()?;
+
+        let follow = cliclack::confirm(MessageType::LogsFollowRequest)
+            .initial_value(true)
+            .interact()?;
+
+        Ok(LogFilter {
+            services: services.into_iter().map(str::to_owned).collect(),
+            level,
+            search: if search.is_empty() {
+                None
+            } else {
+                Some(Regex::new(&search)?)
+            },
+            follow,
+            tail: Some(DEFAULT_TAIL_LINES),
+            ..Default::default()
+        })
+    }
+
+    async fn stream(filter: &LogFilter) -> Result<(), AppError> {
+        let mut child = exec::spawn_docker_compose_logs(filter)?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Unable to read `docker-compose logs` output"))?;
+        let mut stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| anyhow!("Unable to read `docker-compose logs` errors"))?;
+        // Drained concurrently, so a chatty stderr can't block the log stream
+        let stderr = tokio::spawn(async move {
+            let mut buf = String::new();
+            stderr.read_to_string(&mut buf).await.map(|_| buf)
+        });
+        let mut lines = BufReader::new(stdout).lines();
+        let mut stall_detector = ImportStallDetector::default();
+        let mut stall_check = tokio::time::interval(IMPORT_STALL_CHECK_INTERVAL);
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let interrupted = loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(raw) = line? else {
+                        break false;
+                    };
+
+                    let line = LogLine::parse(&raw);
+                    if filter.matches(&line) {
+                        // Raw lines would break the one event per line contract
+                        if output::is_jsonl() {
+                            output::emit(output::Event::Report {
+                                name: "logs",
+                                data: serde_json::to_value(&line)?,
+                            })?;
+                        } else {
+                            println!("{}", line.render());
+                        }
+                    }
+
+                    stall_detector.observe(&line);
+                }
+                _ = stall_check.tick(), if filter.follow => {
+                    if let Some(elapsed) = stall_detector.check() {
+                        output::log::warning(MessageType::LogsImportStalled { elapsed })?;
+                    }
+                }
+                _ = &mut ctrl_c => {
+                    break true;
+                }
+            }
+        };
+
+        if interrupted {
+            child.kill().await.or_else(|e| {
+                if e.kind() == std::io::ErrorKind::InvalidInput {
+                    // Process has already exited
+                    Ok(())
+                } else {
+                    Err(e)
+                }
+            })?;
+
+            return Ok(());
+        }
+
+        let status = child.wait().await?;
+        if !status.success() {
+            let stderr = stderr.await.ok().and_then(Result::ok).unwrap_or_default();
+            return Err(anyhow!(
+                "Run `docker-compose logs` failure ({:?}). Error: {}",
+                status.code(),
+                stderr.trim()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Phase for ViewLogsPhase {
+    fn run(&mut self) -> BoxFuture<'_, Result<(), AppError>> {
+        async {
+            let filter = match self.filter.take() {
+                Some(filter) => filter,
+                None => Self::ask_filter()?,
+            };
+
+            output::log::step(MessageType::LogsStreaming)?;
+
+            Self::stream(&filter).await
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,174 @@
+use alloy::primitives::U256;
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+use crate::error::AppError;
+
+/// OpenEthereum chain specification. Only the fields which are validated are deserialized
+#[derive(Deserialize, Debug)]
+pub struct Chainspec {
+    pub name: String,
+    engine: Map<String, Value>,
+    params: ChainspecParams,
+    genesis: ChainspecGenesis,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChainspecParams {
+    #[serde(rename = "networkID")]
+    network_id: String,
+    #[serde(rename = "chainID")]
+    chain_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ChainspecGenesis {
+    seal: Map<String, Value>,
+    difficulty: String,
+    gas_limit: String,
+}
+
+impl Chainspec {
+    /// Parses and validates chainspec content
+    pub fn parse(content: &[u8]) -> Result<Self, AppError> {
+        let chainspec = serde_json::from_slice::<Self>(content)
+            .map_err(|e| anyhow!("Invalid chainspec: {e}"))?;
+
+        if chainspec.name.is_empty() {
+            return Err(anyhow!("Invalid chainspec: empty `name`").into());
+        }
+        match chainspec.engine.values().next() {
+            Some(Value::Object(_)) if chainspec.engine.len() == 1 => {}
+            _ => {
+                return Err(
+                    anyhow!("Invalid chainspec: `engine` must define exactly one engine").into(),
+                )
+            }
+        }
+        if chainspec.genesis.seal.is_empty() {
+            return Err(anyhow!("Invalid chainspec: empty `genesis.seal`").into());
+        }
+        for (field, value) in [
+            ("genesis.difficulty", &chainspec.genesis.difficulty),
+            ("genesis.gasLimit", &chainspec.genesis.gas_limit),
+        ] {
+            U256::from_str(value)
+                .map_err(|_| anyhow!("Invalid chainspec: `{field}` is not a number: {value}"))?;
+        }
+        chainspec.chain_id()?;
+
+        Ok(chainspec)
+    }
+
+    /// Chain ID used for transaction signing. OpenEthereum falls back to network ID if it's not set
+    pub fn chain_id(&self) -> Result<u64, AppError> {
+        let value = self
+            .params
+            .chain_id
+            .as_ref()
+            .unwrap_or(&self.params.network_id);
+
+        U256::from_str(value)
+            .ok()
+            .and_then(|chain_id| u64::try_from(chain_id).ok())
+            .ok_or_else(|| anyhow!("Invalid chainspec: chain ID is not a number: {value}").into())
+    }
+
+    /// Aura step duration in seconds. With multiple transitions the latest one is used
+    pub fn step_duration(&self) -> Option<u64> {
+        let step_duration = self
+            .engine
+            .get("authorityRound")?
+            .get("params")?
+            .get("stepDuration")?;
+
+        match step_duration {
+            Value::Object(transitions) => transitions
+                .iter()
+                .filter_map(|(block, value)| Some((parse_quantity_str(block)?, value)))
+                .max_by_key(|(block, _)| *block)
+                .and_then(|(_, value)| parse_quantity(value)),
+            value => parse_quantity(value),
+        }
+    }
+
+    /// Fails if the chain ID doesn't match the one pinned for the network
+    pub fn verify_chain_id(&self, expected: Option<u64>) -> Result<(), AppError> {
+        let chain_id = self.chain_id()?;
+
+        match expected {
+            Some(expected) if expected != chain_id => Err(anyhow!(
+                "Chainspec `{}` has chain ID {chain_id}, but {expected} is expected for the selected network",
+                self.name
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parses number given either as JSON number, decimal or `0x` prefixed hex string
+fn parse_quantity(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => parse_quantity_str(string),
+        _ => None,
+    }
+}
+
+fn parse_quantity_str(value: &str) -> Option<u64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chainspec(network_id: &str) -> Vec<u8> {
+        json!({
+            "name": "ambnet",
+            "engine": { "authorityRound": { "params": { "stepDuration": 5 } } },
+            "params": { "networkID": network_id, "maximumExtraDataSize": "0x20" },
+            "genesis": {
+                "seal": { "authorityRound": { "step": "0x0", "signature": "0x00" } },
+                "difficulty": "0x20000",
+                "gasLimit": "0x7A1200"
+            },
+            "accounts": {}
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_chainspec_validation() {
+        let spec = Chainspec::parse(&chainspec("0x414e")).unwrap();
+        assert_eq!(spec.name, "ambnet");
+        assert_eq!(spec.chain_id().unwrap(), 16718);
+        assert!(spec.verify_chain_id(Some(16718)).is_ok());
+        assert!(spec.verify_chain_id(None).is_ok());
+        assert!(spec.verify_chain_id(Some(22040)).is_err());
+        assert_eq!(spec.step_duration(), Some(5));
+
+        assert!(Chainspec::parse(&chainspec("not a number")).is_err());
+        assert!(Chainspec::parse(b"<html>502 Bad Gateway</html>").is_err());
+        assert!(Chainspec::parse(br#"{"name": "ambnet"}"#).is_err());
+
+        let mut no_engine = serde_json::from_slice::<Value>(&chainspec("0x414e")).unwrap();
+        no_engine["engine"] = json!({});
+        assert!(Chainspec::parse(no_engine.to_string().as_bytes()).is_err());
+
+        let mut transitions = serde_json::from_slice::<Value>(&chainspec("0x414e")).unwrap();
+        transitions["engine"]["authorityRound"]["params"]["stepDuration"] =
+            json!({ "0": 5, "0x100": "0x3" });
+        let spec = Chainspec::parse(transitions.to_string().as_bytes()).unwrap();
+        assert_eq!(spec.step_duration(), Some(3));
+    }
+}
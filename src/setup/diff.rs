@@ -0,0 +1,151 @@
+use console::style;
+use similar::TextDiff;
+use std::{fmt::Display, path::Path};
+
+use crate::{error::AppError, messages::MessageType};
+
+const DIFF_CONTEXT_LINES: usize = 3;
+
+pub struct FileDiff {
+    pub name: String,
+    /// `None` if the file doesn't exist yet
+    pub current: Option<String>,
+    /// Unified diff, empty if the file is unchanged
+    pub unified: String,
+}
+
+/// Difference between rendered setup and the current output directory
+pub struct SetupDiff {
+    pub files: Vec<FileDiff>,
+    pub current_image: Option<String>,
+    pub image: String,
+    /// Files with secrets which will be regenerated, their content is never shown
+    pub regenerated_files: Vec<&'static str>,
+}
+
+impl FileDiff {
+    fn is_changed(&self) -> bool {
+        !self.unified.is_empty()
+    }
+}
+
+impl SetupDiff {
+    pub fn has_changes(&self) -> bool {
+        self.files.iter().any(FileDiff::is_changed) || !self.regenerated_files.is_empty()
+    }
+}
+
+impl Display for SetupDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let image = match &self.current_image {
+            Some(current_image) if current_image == &self.image => {
+                MessageType::DiffImageUnchanged { image: &self.image }
+            }
+            Some(current_image) => MessageType::DiffImageChanged {
+                current: style(current_image).red().to_string(),
+                image: style(&self.image).green().to_string(),
+            },
+            None => MessageType::DiffImageNew {
+                image: style(&self.image).green().to_string(),
+            },
+        };
+        writeln!(f, "{image}")?;
+
+        for file in &self.files {
+            let file_name = file.name.as_str();
+            let status = match (&file.current, file.is_changed()) {
+                (None, _) => MessageType::DiffFileNew { file: file_name },
+                (Some(_), false) => MessageType::DiffFileUnchanged { file: file_name },
+                (Some(_), true) => MessageType::DiffFileChanged { file: file_name },
+            };
+            writeln!(f, "{status}")?;
+        }
+        if !self.regenerated_files.is_empty() {
+            writeln!(
+                f,
+                "{}",
+                MessageType::DiffFilesRegenerated {
+                    files: self.regenerated_files.join(", "),
+                }
+            )?;
+        }
+
+        for file in self.files.iter().filter(|file| file.is_changed()) {
+            writeln!(f)?;
+            for line in file.unified.lines() {
+                if line.starts_with("+++") || line.starts_with("---") {
+                    writeln!(f, "{}", style(line).bold())?;
+                } else if line.starts_with('+') {
+                    writeln!(f, "{}", style(line).green())?;
+                } else if line.starts_with('-') {
+                    writeln!(f, "{}", style(line).red())?;
+                } else if line.starts_with("@@") {
+                    writeln!(f, "{}", style(line).cyan())?;
+                } else {
+                    writeln!(f, "{line}")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the file if it exists
+pub async fn read_current(path: &Path) -> Result<Option<String>, AppError> {
+    match tokio::fs::read(path).await {
+        Ok(content) => Ok(Some(String::from_utf8_lossy(&content).into_owned())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Compares the file at `path` with `new` content
+pub async fn diff_file(path: &Path, new: &str) -> Result<FileDiff, AppError> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let current = read_current(path).await?;
+    let unified = unified_diff(&name, current.as_deref(), new);
+
+    Ok(FileDiff {
+        name,
+        current,
+        unified,
+    })
+}
+
+fn unified_diff(name: &str, current: Option<&str>, new: &str) -> String {
+    let current_name = match current {
+        Some(_) => format!("a/{name}"),
+        None => "/dev/null".to_owned(),
+    };
+
+    TextDiff::from_lines(current.unwrap_or_default(), new)
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(&current_name, &format!("b/{name}"))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let current = "services:\n  parity:\n    image: openethereum:v3.3.3-amb1.2.4\n";
+        let new = "services:\n  parity:\n    image: openethereum:v3.3.3-amb1.2.5\n";
+
+        let diff = unified_diff("docker-compose.yml", Some(current), new);
+        assert!(diff.starts_with("--- a/docker-compose.yml\n+++ b/docker-compose.yml\n"));
+        assert!(diff.contains("-    image: openethereum:v3.3.3-amb1.2.4\n"));
+        assert!(diff.contains("+    image: openethereum:v3.3.3-amb1.2.5\n"));
+
+        assert!(unified_diff("docker-compose.yml", Some(new), new).is_empty());
+        assert!(
+            unified_diff("parity_config.toml", None, "[parity]\n").starts_with("--- /dev/null\n")
+        );
+    }
+}
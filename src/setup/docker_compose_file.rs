@@ -1,65 +1,175 @@
-use alloy::primitives::Address;
 use anyhow::anyhow;
 use std::path::PathBuf;
-use yaml_rust2::{Yaml, YamlLoader};
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
 
-use crate::{config::Network, error::AppError};
+use crate::error::AppError;
 
-use super::utils;
+use super::{overrides, template, template::TemplateContext, utils};
+
+const PARITY_IMAGE_PATH: &str = "services.parity.image";
+const VALIDATOR_VERSION_PREFIX: &str = "Apollo ";
+/// Parity refuses to start if `extra_data` is longer than 32 bytes
+const MAX_VALIDATOR_VERSION_LENGTH: usize = 32;
+/// Longest image tag which fits into validator version
+pub const MAX_IMAGE_TAG_LENGTH: usize =
+    MAX_VALIDATOR_VERSION_LENGTH - VALIDATOR_VERSION_PREFIX.len();
 
 pub struct DockerComposeFile {
     pub validator_version: String,
-    output_file_path: PathBuf,
-    content: String,
+    /// Parity docker image with tag
+    pub image: String,
+    /// Parity image from the setup template, before overrides and pinning are applied
+    pub template_image: String,
+    pub output_file_path: PathBuf,
+    pub content: String,
 }
 
 impl DockerComposeFile {
     pub async fn new(
         input_file_path: PathBuf,
         output_file_path: PathBuf,
-        network_name: &str,
-        network: &Network,
-        address: &Address,
+        overrides_file_path: PathBuf,
+        pinned_image: Option<&str>,
+        context: &TemplateContext,
     ) -> Result<Self, AppError> {
-        let bytes = tokio::fs::read(&input_file_path).await?;
-        let raw_text = std::str::from_utf8(&bytes)?;
-        let yaml_nodes = YamlLoader::load_from_str(raw_text)?;
-        let template = raw_text.to_owned();
-
-        for node in yaml_nodes {
-            if let Some(Yaml::String(image)) =
-                utils::yaml_find_hash_node(&node, "services.parity.image")
-            {
-                let Some((_, version)) = image.split_once(":") else {
-                    continue;
-                };
+        let mut content = template::render_file(&input_file_path, context).await?;
+        let Some(template_image) = Self::find_image(&content) else {
+            return Err(anyhow!(
+                "Validator version not found in docker template file '{input_file_path:?}'"
+            )
+            .into());
+        };
 
+        // Overrides are merged before the version lookup, so the operator may pin parity image
+        if let Some(overrides) = overrides::read(&overrides_file_path).await? {
+            content = overrides::merge_yaml(&content, &overrides)?;
+        }
+        // Image pinned with `version pin` takes precedence over overrides
+        if let Some(image) = pinned_image {
+            content = Self::replace_image(&content, image)?;
+        }
+
+        if let Some(image) = Self::find_image(&content) {
+            if let Some(version) = crate::utils::image_tag(&image) {
                 return Ok(DockerComposeFile {
-                    validator_version: format!("Apollo {version}"),
+                    validator_version: validator_version(version),
+                    image,
+                    template_image,
                     output_file_path,
-                    content: template
-                        .replace("<ENTER_YOUR_ADDRESS_HERE>", &format!("{:?}", address))
-                        .replace("<ENTER_NETWORK_NAME_HERE>", network_name)
-                        .replace("<ENTER_DOMAIN_HERE>", &network.domain)
-                        .replace(
-                            "<ENTER_HOST_OUTPUT_PATH_HERE>",
-                            std::env::var("HOST_OUTPUT_DIRECTORY")
-                                .as_deref()
-                                .unwrap_or("."),
-                        ),
+                    content,
                 });
             }
         }
 
         Err(
-            anyhow!("Validator version not found in docker template file '{input_file_path:?}'")
+            anyhow!("Validator version not found in docker compose file '{output_file_path:?}'")
                 .into(),
         )
     }
 
+    /// Finds parity image in docker compose file content
+    pub fn find_image(content: &str) -> Option<String> {
+        YamlLoader::load_from_str(content)
+            .ok()?
+            .iter()
+            .find_map(
+                |node| match utils::yaml_find_hash_node(node, PARITY_IMAGE_PATH) {
+                    Some(Yaml::String(image)) => Some(image.clone()),
+                    _ => None,
+                },
+            )
+    }
+
+    fn replace_image(content: &str, image: &str) -> Result<String, AppError> {
+        let mut docs = YamlLoader::load_from_str(content)?;
+        let Some(doc) = docs.first_mut() else {
+            return Err(anyhow!("Docker compose file is empty").into());
+        };
+        let Some(node) = utils::yaml_find_hash_node_mut(doc, PARITY_IMAGE_PATH) else {
+            return Err(anyhow!("Parity image not found in docker compose file").into());
+        };
+        *node = Yaml::String(image.to_owned());
+
+        let mut content = String::new();
+        YamlEmitter::new(&mut content).dump(doc)?;
+        content.push('\n');
+        Ok(content)
+    }
+
     pub async fn save(&self) -> Result<(), AppError> {
-        tokio::fs::write(&self.output_file_path, &self.content)
+        crate::utils::write_file_atomic(&self.output_file_path, &self.content)
             .await
             .map_err(AppError::from)
     }
 }
+
+/// Validator version written to parity `extra_data`. Tag of the image set with overrides
+/// isn't validated, so it's truncated to fit
+fn validator_version(tag: &str) -> String {
+    let length = (0..=MAX_IMAGE_TAG_LENGTH.min(tag.len()))
+        .rev()
+        .find(|&length| tag.is_char_boundary(length))
+        .unwrap_or_default();
+    format!("{VALIDATOR_VERSION_PREFIX}{}", &tag[..length])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::image_tag;
+
+    #[test]
+    fn test_replace_image() {
+        let content = "
+services:
+  parity:
+    image: ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4
+    restart: unless-stopped
+";
+        let replaced = DockerComposeFile::replace_image(
+            content,
+            "ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.3",
+        )
+        .unwrap();
+        assert_eq!(
+            DockerComposeFile::find_image(&replaced).as_deref(),
+            Some("ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.3")
+        );
+        assert!(replaced.contains("restart: unless-stopped"));
+        assert!(DockerComposeFile::replace_image("services: {}", "openethereum:v1").is_err());
+    }
+
+    #[test]
+    fn test_image_tag() {
+        assert_eq!(
+            image_tag("ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4"),
+            Some("v3.3.3-amb1.2.4")
+        );
+        assert_eq!(
+            image_tag("registry.local:5000/openethereum:test"),
+            Some("test")
+        );
+        assert_eq!(image_tag("registry.local:5000/openethereum"), None);
+        assert_eq!(image_tag("openethereum"), None);
+    }
+
+    #[test]
+    fn test_validator_version() {
+        assert_eq!(
+            validator_version("v3.3.3-amb1.2.4"),
+            "Apollo v3.3.3-amb1.2.4"
+        );
+        assert_eq!(
+            validator_version(&"a".repeat(MAX_IMAGE_TAG_LENGTH)).len(),
+            MAX_VALIDATOR_VERSION_LENGTH
+        );
+        assert_eq!(
+            validator_version(&"a".repeat(128)).len(),
+            MAX_VALIDATOR_VERSION_LENGTH
+        );
+        assert_eq!(
+            validator_version(&"é".repeat(20)).len(),
+            MAX_VALIDATOR_VERSION_LENGTH - 1
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+const IMAGE_HISTORY_FILE_NAME: &str = "image_history.json";
+/// Older entries are dropped, so the file doesn't grow forever
+const IMAGE_HISTORY_MAX_ENTRIES: usize = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageHistoryEntry {
+    pub image: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+/// Parity images applied to the node, oldest first
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ImageHistory {
+    pub entries: Vec<ImageHistoryEntry>,
+}
+
+impl ImageHistory {
+    fn path(output_dir: &Path) -> PathBuf {
+        output_dir.join(IMAGE_HISTORY_FILE_NAME)
+    }
+
+    /// Reads history from output directory, missing file means empty history
+    pub async fn read() -> Result<Self, AppError> {
+        Self::read_in(&crate::utils::output_dir()).await
+    }
+
+    pub async fn read_in(output_dir: &Path) -> Result<Self, AppError> {
+        match tokio::fs::read(Self::path(output_dir)).await {
+            Ok(content) => serde_json::from_slice(&content).map_err(AppError::from),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn write(&self) -> Result<(), AppError> {
+        self.write_in(&crate::utils::output_dir()).await
+    }
+
+    pub async fn write_in(&self, output_dir: &Path) -> Result<(), AppError> {
+        crate::utils::write_file_atomic(&Self::path(output_dir), serde_json::to_vec_pretty(self)?)
+            .await
+            .map_err(AppError::from)
+    }
+
+    pub fn push(&mut self, image: String) {
+        self.entries.push(ImageHistoryEntry {
+            image,
+            applied_at: Utc::now(),
+        });
+
+        let overflow = self.entries.len().saturating_sub(IMAGE_HISTORY_MAX_ENTRIES);
+        self.entries.drain(..overflow);
+    }
+
+    /// The latest applied image which differs from `current` one
+    pub fn previous(&self, current: &str) -> Option<&ImageHistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.image != current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previous_image() {
+        let mut history = ImageHistory::default();
+        assert!(history.previous("openethereum:v3.3.3-amb1.2.4").is_none());
+
+        history.push("openethereum:v3.3.3-amb1.2.3".to_owned());
+        history.push("openethereum:v3.3.3-amb1.2.4".to_owned());
+        history.push("openethereum:v3.3.3-amb1.2.4".to_owned());
+        assert_eq!(
+            history
+                .previous("openethereum:v3.3.3-amb1.2.4")
+                .map(|entry| entry.image.as_str()),
+            Some("openethereum:v3.3.3-amb1.2.3")
+        );
+
+        // Rolled back image becomes the latest one, so the next rollback returns to the newer image
+        history.push("openethereum:v3.3.3-amb1.2.3".to_owned());
+        assert_eq!(
+            history
+                .previous("openethereum:v3.3.3-amb1.2.3")
+                .map(|entry| entry.image.as_str()),
+            Some("openethereum:v3.3.3-amb1.2.4")
+        );
+
+        for i in 0..IMAGE_HISTORY_MAX_ENTRIES {
+            history.push(format!("openethereum:{i}"));
+        }
+        assert_eq!(history.entries.len(), IMAGE_HISTORY_MAX_ENTRIES);
+        assert_eq!(history.entries[0].image, "openethereum:0");
+    }
+}
@@ -6,7 +6,6 @@ use scrypt::{scrypt, Params as ScryptParams};
 use serde::{Deserialize, Serialize};
 use sha3::{digest::Update, Digest, Keccak256};
 use std::path::Path;
-use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 const DEFAULT_CIPHER: &str = "aes-128-ctr";
@@ -91,9 +90,8 @@ where
     };
     let contents = serde_json::to_string(&keystore)?;
 
-    // Create a file in write-only mode, to store the encrypted JSON keystore.
-    let mut file = tokio::fs::File::create(dir.as_ref().join(&name)).await?;
-    file.write_all(contents.as_bytes()).await?;
+    // Replace the keystore atomically, so running parity never reads a partially written file.
+    crate::utils::write_file_atomic(&dir.as_ref().join(&name), contents.as_bytes()).await?;
 
     Ok(id.to_string())
 }
@@ -1,29 +1,46 @@
+mod chainspec;
+mod diff;
 mod docker_compose_file;
+mod image_history;
 pub mod keystore;
+mod overrides;
 mod parity_config_file;
+mod snapshot;
+mod template;
 pub mod utils;
 
 use alloy::primitives::Address;
 use anyhow::anyhow;
-use futures::StreamExt;
 use k256::ecdsa::SigningKey;
 use rand::rngs::OsRng;
-use serde::Deserialize;
 use std::{
     net::IpAddr,
     path::{Path, PathBuf},
 };
 
-use crate::{config::Network, error::AppError, messages::MessageType, state::State};
+use crate::{
+    config::Network,
+    error::AppError,
+    messages::MessageType,
+    state::State,
+    utils::{output, profile::NodePorts},
+};
+pub use chainspec::Chainspec;
+pub use diff::SetupDiff;
 use docker_compose_file::DockerComposeFile;
+pub use docker_compose_file::MAX_IMAGE_TAG_LENGTH;
+pub use image_history::ImageHistory;
+use overrides::OVERRIDES_DIR_NAME;
 use parity_config_file::ParityConfigFile;
+pub use snapshot::Snapshot;
+use template::TemplateContext;
 
 const DEFAULT_TEMPLATES_PATH: &str = "./setup_templates/";
 const CHAIN_DESCRIPTION_FILE_NAME: &str = "./chain.json";
 
 const DOCKER_FILE_NAME: &str = "./docker-compose.yml";
 const PARITY_CONFIG_FILE_NAME: &str = "./parity_config.toml";
-const PASSWORD_FILE_NAME: &str = "password.pwds";
+pub const PASSWORD_FILE_NAME: &str = "password.pwds";
 const KEY_FILE_NAME: &str = "keyfile";
 
 pub struct Setup {
@@ -31,11 +48,70 @@ pub struct Setup {
     pub address: Address,
     private_key: SigningKey,
     ip: IpAddr,
+    pub ports: NodePorts,
+    /// Parity image pinned by operator, see [`State::parity_image`]
+    parity_image: Option<String>,
+    /// Reuse cached chainspec instead of downloading it
+    offline: bool,
+}
+
+/// Setup output rendered in memory, see [`Setup::render`]
+pub struct RenderedSetup {
+    chainspec_file_path: PathBuf,
+    chainspec_content: Vec<u8>,
+    docker_compose_file: DockerComposeFile,
+    parity_config_file: ParityConfigFile,
+    /// Password and keyfile are missing or don't match the current private key
+    regenerate_keystore: bool,
+}
+
+/// Parity images of the node, see [`Setup::parity_images`]
+pub struct ParityImages {
+    /// Image in the current `docker-compose.yml`
+    pub current: Option<String>,
+    /// Image the next setup run applies
+    pub target: String,
+    /// Image from the setup template
+    pub template: String,
 }
 
-#[derive(Deserialize)]
-struct Chainspec {
-    name: String,
+impl RenderedSetup {
+    /// Compares rendered files with the ones currently stored in output directory
+    pub async fn diff(&self) -> Result<SetupDiff, AppError> {
+        let current_docker_compose =
+            diff::read_current(&self.docker_compose_file.output_file_path).await?;
+
+        let files = vec![
+            diff::diff_file(
+                &self.chainspec_file_path,
+                &String::from_utf8_lossy(&self.chainspec_content),
+            )
+            .await?,
+            diff::diff_file(
+                &self.docker_compose_file.output_file_path,
+                &self.docker_compose_file.content,
+            )
+            .await?,
+            diff::diff_file(
+                &self.parity_config_file.output_file_path,
+                &self.parity_config_file.content,
+            )
+            .await?,
+        ];
+
+        Ok(SetupDiff {
+            files,
+            current_image: current_docker_compose
+                .as_deref()
+                .and_then(DockerComposeFile::find_image),
+            image: self.docker_compose_file.image.clone(),
+            regenerated_files: if self.regenerate_keystore {
+                vec![PASSWORD_FILE_NAME, KEY_FILE_NAME]
+            } else {
+                Vec::new()
+            },
+        })
+    }
 }
 
 impl Setup {
@@ -45,6 +121,9 @@ impl Setup {
             address: Some(address),
             private_key: Some(private_key),
             ip: Some(ip),
+            parity_image,
+            ports,
+            ..
         } = state
         else {
             return Err(anyhow!("State is incomplete").into());
@@ -55,9 +134,17 @@ impl Setup {
             address,
             private_key,
             ip,
+            ports: ports.unwrap_or_default(),
+            parity_image,
+            offline: false,
         })
     }
 
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
     fn templates_path() -> PathBuf {
         std::env::var("TEMPLATE_DIRECTORY")
             .map(PathBuf::from)
@@ -65,47 +152,87 @@ impl Setup {
     }
 
     pub async fn run(&self) -> Result<(), AppError> {
+        let rendered = self.render().await?;
+        self.apply(&rendered).await
+    }
+
+    /// Downloads chainspec and renders configuration files in memory without touching output directory
+    pub async fn render(&self) -> Result<RenderedSetup, AppError> {
+        let output_dir = crate::utils::output_dir();
+
+        let chainspec_file_path = output_dir.join(CHAIN_DESCRIPTION_FILE_NAME);
+        let chainspec_content = if self.offline {
+            tokio::fs::read(&chainspec_file_path).await.map_err(|e| {
+                anyhow!("Cached chainspec {chainspec_file_path:?} can't be used offline: {e}")
+            })?
+        } else {
+            // Kept in memory until validated, so a broken download never replaces a working chainspec
+            reqwest::get(&self.network.chainspec)
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec()
+        };
+        let chainspec = Chainspec::parse(&chainspec_content)?;
+        chainspec.verify_chain_id(self.network.chain_id)?;
+
+        let docker_compose_file = self.docker_compose_file(&output_dir, &chainspec).await?;
+        let parity_config_file = self
+            .parity_config_file(&output_dir, &chainspec, &docker_compose_file)
+            .await?;
+
+        Ok(RenderedSetup {
+            chainspec_file_path,
+            chainspec_content,
+            docker_compose_file,
+            parity_config_file,
+            regenerate_keystore: !self.is_keystore_valid(&output_dir).await,
+        })
+    }
+
+    /// Writes rendered files into output directory. Password and keyfile are kept unless they
+    /// don't match the current private key
+    pub async fn apply(&self, rendered: &RenderedSetup) -> Result<(), AppError> {
         let output_dir = crate::utils::output_dir();
 
         if !tokio::fs::try_exists(&output_dir).await? {
             tokio::fs::create_dir_all(&output_dir).await?;
         }
 
-        let chainspec = self.download_and_save_chainspec_file(&output_dir).await?;
-
-        let docker_template_file_path = Self::templates_path()
-            .join("apollo")
-            .join(&chainspec.name)
-            .join(DOCKER_FILE_NAME);
+        let current_chainspec = diff::read_current(&rendered.chainspec_file_path).await?;
+        let current_image = diff::read_current(&rendered.docker_compose_file.output_file_path)
+            .await?
+            .as_deref()
+            .and_then(DockerComposeFile::find_image);
+        if matches!(&current_chainspec, Some(current) if current.as_bytes() != rendered.chainspec_content)
+        {
+            output::log::info(MessageType::ChainspecUpdated)?;
+        }
+        crate::utils::write_file_atomic(&rendered.chainspec_file_path, &rendered.chainspec_content)
+            .await?;
+        rendered.docker_compose_file.save().await?;
+        rendered.parity_config_file.save().await?;
 
-        let parity_config_template_file_path = Self::templates_path()
-            .join("apollo")
-            .join(&chainspec.name)
-            .join(PARITY_CONFIG_FILE_NAME);
+        if current_image.as_ref() != Some(&rendered.docker_compose_file.image) {
+            let mut history = ImageHistory::read().await?;
+            history.push(rendered.docker_compose_file.image.clone());
+            history.write().await?;
+        }
 
-        let docker_compose_file = DockerComposeFile::new(
-            docker_template_file_path,
-            output_dir.join(DOCKER_FILE_NAME),
-            &chainspec.name,
-            &self.network,
-            &self.address,
-        )
-        .await?;
-        docker_compose_file.save().await?;
+        if rendered.regenerate_keystore {
+            self.rotate_keystore().await?;
+        }
 
-        let parity_config_file = ParityConfigFile::new(
-            parity_config_template_file_path,
-            output_dir.join(PARITY_CONFIG_FILE_NAME),
-            &self.address,
-            &self.ip,
-            &docker_compose_file.validator_version,
-        )
-        .await?;
-        parity_config_file.save().await?;
+        output::note("Setup status", MessageType::SetupCompleted).map_err(AppError::from)
+    }
 
+    /// Generates new password and re-encrypts the private key with it
+    pub async fn rotate_keystore(&self) -> Result<(), AppError> {
+        let output_dir = crate::utils::output_dir();
         let random_password = utils::generate_password();
-        tokio::fs::write(output_dir.join(PASSWORD_FILE_NAME), &random_password).await?;
 
+        // Keyfile goes first: if password write fails, the mismatch is detected and fixed on the next run
         keystore::encrypt_key(
             &output_dir,
             &mut OsRng,
@@ -114,28 +241,130 @@ impl Setup {
             Some(KEY_FILE_NAME),
         )
         .await?;
+        crate::utils::write_file_atomic(&output_dir.join(PASSWORD_FILE_NAME), &random_password)
+            .await?;
 
-        let saved_key = eth_keystore::decrypt_key(output_dir.join(KEY_FILE_NAME), random_password);
-        if !matches!(saved_key, Ok(private) if sha3::digest::generic_array::GenericArray::clone_from_slice(private.as_slice()) == self.private_key.to_bytes())
-        {
+        if !self.is_keystore_valid(&output_dir).await {
             return Err(anyhow::anyhow!("Stored private key mismatch!").into());
         }
 
-        cliclack::note("Setup status", MessageType::SetupCompleted).map_err(AppError::from)
+        Ok(())
+    }
+
+    /// Checks that the stored keyfile decrypts with the stored password to the current private key
+    async fn is_keystore_valid(&self, output_dir: &Path) -> bool {
+        let output_dir = output_dir.to_owned();
+        let private_key = self.private_key.to_bytes();
+
+        // Key derivation is deliberately slow, so it must not stall the async runtime
+        tokio::task::spawn_blocking(move || {
+            let Ok(password) = std::fs::read_to_string(output_dir.join(PASSWORD_FILE_NAME)) else {
+                return false;
+            };
+
+            let saved_key = eth_keystore::decrypt_key(output_dir.join(KEY_FILE_NAME), password);
+            matches!(saved_key, Ok(private) if private.as_slice() == private_key.as_slice())
+        })
+        .await
+        .unwrap_or(false)
+    }
+
+    /// Regenerates only `parity_config.toml` from the previously downloaded chainspec,
+    /// e.g. after the node IP has changed
+    pub async fn regenerate_parity_config(&self) -> Result<(), AppError> {
+        let output_dir = crate::utils::output_dir();
+        let chainspec = Self::cached_chainspec().await?;
+
+        let docker_compose_file = self.docker_compose_file(&output_dir, &chainspec).await?;
+        self.parity_config_file(&output_dir, &chainspec, &docker_compose_file)
+            .await?
+            .save()
+            .await
+    }
+
+    /// Current, target and template parity images. Uses the previously downloaded chainspec
+    pub async fn parity_images(&self) -> Result<ParityImages, AppError> {
+        let output_dir = crate::utils::output_dir();
+        let chainspec = Self::cached_chainspec().await?;
+        let docker_compose_file = self.docker_compose_file(&output_dir, &chainspec).await?;
+
+        Ok(ParityImages {
+            current: diff::read_current(&docker_compose_file.output_file_path)
+                .await?
+                .as_deref()
+                .and_then(DockerComposeFile::find_image),
+            target: docker_compose_file.image,
+            template: docker_compose_file.template_image,
+        })
+    }
+
+    /// Chain ID the node must run without asking the network RPC: the pinned one, or the one
+    /// of the cached chainspec when offline. `None` if the RPC has to be asked
+    pub async fn known_chain_id(network: &Network, offline: bool) -> Result<Option<u64>, AppError> {
+        match network.chain_id {
+            Some(chain_id) => Ok(Some(chain_id)),
+            None if offline => Self::cached_chainspec().await?.chain_id().map(Some),
+            None => Ok(None),
+        }
     }
 
-    async fn download_and_save_chainspec_file(
+    /// Chainspec downloaded by the previous setup run
+    pub async fn cached_chainspec() -> Result<Chainspec, AppError> {
+        let path = crate::utils::output_dir().join(CHAIN_DESCRIPTION_FILE_NAME);
+        let content = tokio::fs::read(&path)
+            .await
+            .map_err(|e| anyhow!("Chainspec {path:?} not found, run setup first: {e}"))?;
+
+        Chainspec::parse(&content)
+    }
+
+    async fn docker_compose_file(
         &self,
         output_dir: &Path,
-    ) -> Result<Chainspec, AppError> {
-        let chain_spec_file_path = output_dir.join(CHAIN_DESCRIPTION_FILE_NAME);
-        let mut chain_spec_file = tokio::fs::File::create(&chain_spec_file_path).await?;
-        let mut chain_spec_stream = reqwest::get(&self.network.chainspec).await?.bytes_stream();
-        while let Some(chunk) = chain_spec_stream.next().await {
-            tokio::io::copy(&mut chunk?.as_ref(), &mut chain_spec_file).await?;
-        }
+        chainspec: &Chainspec,
+    ) -> Result<DockerComposeFile, AppError> {
+        DockerComposeFile::new(
+            Self::templates_path()
+                .join("apollo")
+                .join(&chainspec.name)
+                .join(DOCKER_FILE_NAME),
+            output_dir.join(DOCKER_FILE_NAME),
+            output_dir.join(OVERRIDES_DIR_NAME).join(DOCKER_FILE_NAME),
+            self.parity_image.as_deref(),
+            &self.template_context(chainspec),
+        )
+        .await
+    }
+
+    async fn parity_config_file(
+        &self,
+        output_dir: &Path,
+        chainspec: &Chainspec,
+        docker_compose_file: &DockerComposeFile,
+    ) -> Result<ParityConfigFile, AppError> {
+        ParityConfigFile::new(
+            Self::templates_path()
+                .join("apollo")
+                .join(&chainspec.name)
+                .join(PARITY_CONFIG_FILE_NAME),
+            output_dir.join(PARITY_CONFIG_FILE_NAME),
+            output_dir
+                .join(OVERRIDES_DIR_NAME)
+                .join(PARITY_CONFIG_FILE_NAME),
+            &self
+                .template_context(chainspec)
+                .with_validator_version(docker_compose_file.validator_version.clone()),
+        )
+        .await
+    }
 
-        serde_json::from_slice::<Chainspec>(&tokio::fs::read(&chain_spec_file_path).await?)
-            .map_err(AppError::from)
+    fn template_context(&self, chainspec: &Chainspec) -> TemplateContext {
+        TemplateContext::new(
+            &self.address,
+            &self.ip,
+            &self.ports,
+            &chainspec.name,
+            &self.network,
+        )
     }
 }
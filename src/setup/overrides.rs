@@ -0,0 +1,147 @@
+use anyhow::anyhow;
+use std::path::Path;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::error::AppError;
+
+/// Directory inside output directory with operator overrides for generated files.
+/// Every file there has the same name as the generated file it's merged into
+pub const OVERRIDES_DIR_NAME: &str = "overrides";
+
+/// Reads overrides file if it exists
+pub async fn read(path: &Path) -> Result<Option<String>, AppError> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Deep merges YAML `overrides` into `base`. Mappings are merged recursively, any other value
+/// (including sequences) replaces the base one, and `null` removes the key
+pub fn merge_yaml(base: &str, overrides: &str) -> Result<String, AppError> {
+    let mut base_docs = YamlLoader::load_from_str(base)?;
+    let Some(base_doc) = base_docs.first_mut() else {
+        return Err(anyhow!("Base YAML document is empty").into());
+    };
+
+    for overrides_doc in YamlLoader::load_from_str(overrides)? {
+        merge_yaml_nodes(base_doc, overrides_doc);
+    }
+
+    let mut content = String::new();
+    YamlEmitter::new(&mut content).dump(base_doc)?;
+    content.push('\n');
+    Ok(content)
+}
+
+/// Deep merges TOML `overrides` into `base`. Tables are merged recursively, any other value
+/// (including arrays) replaces the base one
+pub fn merge_toml(base: &str, overrides: &str) -> Result<String, AppError> {
+    let mut base_table = base.parse::<toml::Table>()?;
+    merge_toml_tables(&mut base_table, overrides.parse::<toml::Table>()?);
+
+    toml::to_string(&base_table).map_err(AppError::from)
+}
+
+fn merge_yaml_nodes(base: &mut Yaml, overrides: Yaml) {
+    match (base, overrides) {
+        (Yaml::Hash(base), Yaml::Hash(overrides)) => {
+            for (key, value) in overrides {
+                if value.is_null() {
+                    base.remove(&key);
+                    continue;
+                }
+
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_yaml_nodes(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+fn merge_toml_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(table)) => {
+                merge_toml_tables(base_table, table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_yaml() {
+        let base = "
+services:
+  parity:
+    image: ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4
+    restart: unless-stopped
+    ports:
+      - '30303:30303/tcp'
+  ethstats-client:
+    image: ghcr.io/ambrosus/eth-net-intelligence-api
+";
+        let overrides = "
+services:
+  parity:
+    mem_limit: 4g
+    ports:
+      - '30304:30303/tcp'
+    logging:
+      driver: local
+  ethstats-client: ~
+";
+        let merged = YamlLoader::load_from_str(&merge_yaml(base, overrides).unwrap()).unwrap();
+        let parity = &merged[0]["services"]["parity"];
+        assert_eq!(
+            parity["image"].as_str(),
+            Some("ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4")
+        );
+        assert_eq!(parity["mem_limit"].as_str(), Some("4g"));
+        assert_eq!(parity["logging"]["driver"].as_str(), Some("local"));
+        assert_eq!(parity["ports"].as_vec().map(Vec::len), Some(1));
+        assert_eq!(parity["ports"][0].as_str(), Some("30304:30303/tcp"));
+        assert!(merged[0]["services"]["ethstats-client"].is_badvalue());
+    }
+
+    #[test]
+    fn test_merge_toml() {
+        let base = r#"
+[parity]
+chain = "/app/chain.json"
+
+[rpc]
+apis = ["web3", "eth", "net", "parity"]
+interface = "all"
+"#;
+        let overrides = r#"
+[rpc]
+apis = ["web3", "eth", "net", "parity", "traces"]
+
+[footprint]
+cache_size = 4096
+"#;
+        let merged = merge_toml(base, overrides)
+            .unwrap()
+            .parse::<toml::Table>()
+            .unwrap();
+        assert_eq!(merged["parity"]["chain"].as_str(), Some("/app/chain.json"));
+        assert_eq!(merged["rpc"]["interface"].as_str(), Some("all"));
+        assert_eq!(merged["rpc"]["apis"].as_array().map(Vec::len), Some(5));
+        assert_eq!(merged["footprint"]["cache_size"].as_integer(), Some(4096));
+        assert!(merge_toml(base, "rpc = [").is_err());
+    }
+}
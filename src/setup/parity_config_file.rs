@@ -1,36 +1,33 @@
-use alloy::primitives::Address;
-use std::{net::IpAddr, path::PathBuf};
+use std::path::PathBuf;
 
+use super::{overrides, template, template::TemplateContext};
 use crate::error::AppError;
 
 pub struct ParityConfigFile {
-    output_file_path: PathBuf,
-    content: String,
+    pub output_file_path: PathBuf,
+    pub content: String,
 }
 
 impl ParityConfigFile {
     pub async fn new(
         input_file_path: PathBuf,
         output_file_path: PathBuf,
-        address: &Address,
-        ip: &IpAddr,
-        validator_version: &str,
+        overrides_file_path: PathBuf,
+        context: &TemplateContext,
     ) -> Result<Self, AppError> {
-        let bytes = tokio::fs::read(&input_file_path).await?;
-        let raw_text = std::str::from_utf8(&bytes)?;
-        let template = raw_text.to_owned();
+        let mut content = template::render_file(&input_file_path, context).await?;
+        if let Some(overrides) = overrides::read(&overrides_file_path).await? {
+            content = overrides::merge_toml(&content, &overrides)?;
+        }
 
         Ok(Self {
             output_file_path,
-            content: template
-                .replace("<TYPE_YOUR_ADDRESS_HERE>", &format!("{:?}", address))
-                .replace("<TYPE_YOUR_IP_HERE>", &ip.to_string())
-                .replace("<TYPE_EXTRA_DATA_HERE>", validator_version),
+            content,
         })
     }
 
     pub async fn save(&self) -> Result<(), AppError> {
-        tokio::fs::write(&self.output_file_path, &self.content)
+        crate::utils::write_file_atomic(&self.output_file_path, &self.content)
             .await
             .map_err(AppError::from)
     }
@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use super::{
+    docker_compose_file::DockerComposeFile, image_history::ImageHistory,
+    CHAIN_DESCRIPTION_FILE_NAME, DOCKER_FILE_NAME, KEY_FILE_NAME, PARITY_CONFIG_FILE_NAME,
+    PASSWORD_FILE_NAME,
+};
+use crate::error::AppError;
+
+/// Directory inside output directory with the files of the last working setup
+const SNAPSHOT_DIR_NAME: &str = ".snapshot";
+const SNAPSHOT_FILES: [&str; 5] = [
+    CHAIN_DESCRIPTION_FILE_NAME,
+    DOCKER_FILE_NAME,
+    PARITY_CONFIG_FILE_NAME,
+    PASSWORD_FILE_NAME,
+    KEY_FILE_NAME,
+];
+
+/// Copy of the generated files taken before an update, so it can be reverted
+pub struct Snapshot {
+    output_dir: PathBuf,
+    dir: PathBuf,
+    /// Parity image of the snapshotted `docker-compose.yml`
+    pub image: Option<String>,
+}
+
+impl Snapshot {
+    /// Copies the current generated files, replacing the previous snapshot
+    pub async fn take() -> Result<Self, AppError> {
+        Self::take_in(crate::utils::output_dir()).await
+    }
+
+    async fn take_in(output_dir: PathBuf) -> Result<Self, AppError> {
+        let dir = output_dir.join(SNAPSHOT_DIR_NAME);
+
+        match tokio::fs::remove_dir_all(&dir).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let mut image = None;
+        for file_name in SNAPSHOT_FILES {
+            let content = match tokio::fs::read(output_dir.join(file_name)).await {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            if file_name == DOCKER_FILE_NAME {
+                image = DockerComposeFile::find_image(&String::from_utf8_lossy(&content));
+            }
+            // Keystore files are copied as well, so snapshot is private
+            crate::utils::write_file_atomic(&dir.join(file_name), content).await?;
+        }
+
+        Ok(Self {
+            output_dir,
+            dir,
+            image,
+        })
+    }
+
+    /// Writes snapshotted files back into output directory. Files which didn't exist when
+    /// the snapshot was taken are left as is
+    pub async fn restore(&self) -> Result<(), AppError> {
+        let current_image = super::diff::read_current(&self.output_dir.join(DOCKER_FILE_NAME))
+            .await?
+            .as_deref()
+            .and_then(DockerComposeFile::find_image);
+
+        for file_name in SNAPSHOT_FILES {
+            let content = match tokio::fs::read(self.dir.join(file_name)).await {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            crate::utils::write_file_atomic(&self.output_dir.join(file_name), content).await?;
+        }
+
+        if let Some(image) = self
+            .image
+            .as_ref()
+            .filter(|image| Some(*image) != current_image.as_ref())
+        {
+            let mut history = ImageHistory::read_in(&self.output_dir).await?;
+            history.push(image.clone());
+            history.write_in(&self.output_dir).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_COMPOSE: &str = "
+services:
+  parity:
+    image: ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4
+";
+
+    fn temp_dir() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("nop-snapshot-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    async fn read(dir: &std::path::Path, file_name: &str) -> Option<String> {
+        tokio::fs::read_to_string(dir.join(file_name)).await.ok()
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore() {
+        let output_dir = temp_dir();
+        tokio::fs::write(output_dir.join(DOCKER_FILE_NAME), DOCKER_COMPOSE)
+            .await
+            .unwrap();
+        tokio::fs::write(output_dir.join(KEY_FILE_NAME), "{\"old\":true}")
+            .await
+            .unwrap();
+
+        let snapshot = Snapshot::take_in(output_dir.clone()).await.unwrap();
+        assert_eq!(
+            snapshot.image.as_deref(),
+            Some("ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4")
+        );
+
+        // Restoring unchanged setup doesn't touch image history
+        snapshot.restore().await.unwrap();
+        assert!(ImageHistory::read_in(&output_dir)
+            .await
+            .unwrap()
+            .entries
+            .is_empty());
+
+        // Update changes the files and creates the ones missing before
+        tokio::fs::write(
+            output_dir.join(DOCKER_FILE_NAME),
+            DOCKER_COMPOSE.replace("amb1.2.4", "amb1.2.5"),
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(output_dir.join(KEY_FILE_NAME), "{\"new\":true}")
+            .await
+            .unwrap();
+        tokio::fs::write(output_dir.join(PARITY_CONFIG_FILE_NAME), "[parity]\n")
+            .await
+            .unwrap();
+
+        snapshot.restore().await.unwrap();
+        assert_eq!(
+            read(&output_dir, DOCKER_FILE_NAME).await.as_deref(),
+            Some(DOCKER_COMPOSE)
+        );
+        assert_eq!(
+            read(&output_dir, KEY_FILE_NAME).await.as_deref(),
+            Some("{\"old\":true}")
+        );
+        assert_eq!(
+            read(&output_dir, PARITY_CONFIG_FILE_NAME).await.as_deref(),
+            Some("[parity]\n")
+        );
+        assert_eq!(read(&output_dir, PASSWORD_FILE_NAME).await, None);
+
+        let history = ImageHistory::read_in(&output_dir).await.unwrap();
+        assert_eq!(
+            history
+                .entries
+                .iter()
+                .map(|entry| entry.image.as_str())
+                .collect::<Vec<_>>(),
+            vec!["ghcr.io/ambrosus/openethereum:v3.3.3-amb1.2.4"]
+        );
+
+        tokio::fs::remove_dir_all(&output_dir).await.unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+use alloy::primitives::Address;
+use anyhow::anyhow;
+use minijinja::{AutoEscape, Environment, UndefinedBehavior};
+use regex::Regex;
+use serde::Serialize;
+use std::{collections::BTreeMap, net::IpAddr, path::Path, sync::LazyLock};
+
+use crate::{
+    config::Network,
+    error::AppError,
+    utils::profile::{self, NodePorts},
+};
+
+/// Environment variables with this prefix are exposed to templates as `custom.<name>` (lowercase)
+const CUSTOM_VARIABLE_ENV_PREFIX: &str = "TEMPLATE_VAR_";
+const DEFAULT_HOST_OUTPUT_PATH: &str = ".";
+
+/// Markers used by templates before the templating engine was introduced
+static LEGACY_MARKER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(?:ENTER|TYPE)_[A-Z0-9_]+_HERE>").unwrap());
+
+/// Variables available in setup templates
+#[derive(Serialize, Debug, Clone)]
+pub struct TemplateContext {
+    pub address: String,
+    pub ip: String,
+    /// Host ports, P2P port is also the one parity listens on, so it advertises the right one
+    pub p2p_port: u16,
+    pub rpc_port: u16,
+    /// Prefix of container names, empty for the default profile
+    pub container_prefix: String,
+    /// Chain name from the chainspec
+    pub network_name: String,
+    pub domain: String,
+    /// Output directory as seen by the docker host
+    pub host_output_path: String,
+    /// Known only after `docker-compose.yml` is rendered, so it's unavailable in that template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validator_version: Option<String>,
+    pub custom: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    pub fn new(
+        address: &Address,
+        ip: &IpAddr,
+        ports: &NodePorts,
+        network_name: &str,
+        network: &Network,
+    ) -> Self {
+        Self {
+            address: format!("{address:?}"),
+            ip: ip.to_string(),
+            p2p_port: ports.p2p,
+            rpc_port: ports.rpc,
+            container_prefix: profile::active().container_prefix(),
+            network_name: network_name.to_owned(),
+            domain: network.domain.clone(),
+            // Default path is relative to `docker-compose.yml`, which is in the output directory
+            host_output_path: std::env::var("HOST_OUTPUT_DIRECTORY")
+                .map(|path| profile::active().host_output_dir(&path))
+                .unwrap_or_else(|_| DEFAULT_HOST_OUTPUT_PATH.to_owned()),
+            validator_version: None,
+            custom: std::env::vars()
+                .filter_map(|(name, value)| {
+                    name.strip_prefix(CUSTOM_VARIABLE_ENV_PREFIX)
+                        .map(|name| (name.to_lowercase(), value))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn with_validator_version(mut self, validator_version: String) -> Self {
+        self.validator_version = Some(validator_version);
+        self
+    }
+}
+
+/// Renders template file. Fails on any undefined variable and on leftovers of legacy markers
+pub async fn render_file(path: &Path, context: &TemplateContext) -> Result<String, AppError> {
+    let bytes = tokio::fs::read(path).await?;
+    render(
+        &path.to_string_lossy(),
+        std::str::from_utf8(&bytes)?,
+        context,
+    )
+}
+
+pub fn render(name: &str, source: &str, context: &TemplateContext) -> Result<String, AppError> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    // Escaping is chosen by file extension by default, which would quote values in YAML templates
+    env.set_auto_escape_callback(|_| AutoEscape::None);
+
+    let rendered = env.template_from_named_str(name, source)?.render(context)?;
+
+    if let Some(marker) = LEGACY_MARKER_REGEX.find(&rendered) {
+        return Err(anyhow!(
+            "Unfilled placeholder `{}` in template '{name}'",
+            marker.as_str()
+        )
+        .into());
+    }
+
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::utils::yaml_find_hash_node;
+    use yaml_rust2::{Yaml, YamlLoader};
+
+    fn context(ip: &str) -> TemplateContext {
+        TemplateContext::new(
+            &"0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            &ip.parse().unwrap(),
+            &NodePorts {
+                p2p: 30304,
+                rpc: 8546,
+            },
+            "ambnet",
+            &Network {
+                domain: "ambrosus.io".to_owned(),
+                rpc: "https://rpc.airdao.io".parse().unwrap(),
+                chainspec: "https://chainspec.ambrosus.io".to_owned(),
+                explorer_url: "https://airdao.io".to_owned(),
+                name: "main".to_owned(),
+                chain_id: Some(16718),
+            },
+        )
+    }
+
+    #[test]
+    fn test_render() {
+        let context = context("203.0.113.7");
+        assert_eq!(
+            render("test", "{{ ip }} {{ address }}\n", &context).unwrap(),
+            "203.0.113.7 0x0000000000000000000000000000000000000001\n"
+        );
+        assert!(render("test", "{{ ipp }}", &context).is_err());
+        assert!(render("test", "{{ validator_version }}", &context).is_err());
+        assert!(render("test", "{{ custom.missing }}", &context).is_err());
+        assert!(render("test", "nat = \"extip:<TYPE_YOUR_IP_HERE>\"", &context).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_render_setup_templates() {
+        let mut templates_count = 0;
+
+        for entry in std::fs::read_dir("./setup_templates/apollo").unwrap() {
+            let chain_dir = entry.unwrap().path();
+
+            let docker_compose = render_file(
+                &chain_dir.join("docker-compose.yml"),
+                &context("203.0.113.7"),
+            )
+            .await
+            .unwrap();
+            let yaml = YamlLoader::load_from_str(&docker_compose).unwrap();
+            let Some(Yaml::String(image)) = yaml_find_hash_node(&yaml[0], "services.parity.image")
+            else {
+                panic!("Parity image not found in {chain_dir:?}");
+            };
+
+            let parity_config = render_file(
+                &chain_dir.join("parity_config.toml"),
+                &context("203.0.113.7").with_validator_version(format!("Apollo {image}")),
+            )
+            .await
+            .unwrap();
+            assert!(parity_config.contains("nat = \"extip:203.0.113.7\""));
+            assert!(parity_config.contains("port = 30304"));
+            assert!(docker_compose.contains("127.0.0.1:8546:8545/tcp"));
+            assert!(docker_compose.contains("'30304:30304/udp'"));
+
+            templates_count += 1;
+        }
+
+        assert!(templates_count > 0);
+    }
+}
@@ -27,6 +27,27 @@ pub fn yaml_find_hash_node<'a>(node: &'a Yaml, path: &str) -> Option<&'a Yaml> {
     }
 }
 
+pub fn yaml_find_hash_node_mut<'a>(node: &'a mut Yaml, path: &str) -> Option<&'a mut Yaml> {
+    let (node_name, path) = match path.split_once(".") {
+        Some((node_name, path)) => (node_name, Some(path)),
+        None => (path, None),
+    };
+
+    match node {
+        Yaml::Hash(map) => {
+            let (_, value) = map
+                .iter_mut()
+                .find(|(key, _)| matches!(key, Yaml::String(name) if name == node_name ))?;
+            let Some(path) = path else {
+                return Some(value);
+            };
+
+            yaml_find_hash_node_mut(value, path)
+        }
+        _ => None,
+    }
+}
+
 pub fn generate_password() -> String {
     format!(
         "0x{}",
@@ -2,12 +2,7 @@ use alloy::primitives::Address;
 use config::ConfigError;
 use k256::ecdsa::SigningKey;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    io::{BufWriter, Write},
-    net::IpAddr,
-    path::PathBuf,
-};
+use std::{net::IpAddr, path::PathBuf};
 
 use crate::{
     config::Network,
@@ -15,15 +10,11 @@ use crate::{
     utils::{
         self,
         config::{ConfigPath, JsonConfig},
+        profile::{self, NodePorts},
     },
 };
 
-const DEFAULT_STATE_PATH: ConfigPath<'_> = ConfigPath::Relative {
-    root: "./",
-    path: "./state.json",
-};
-
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct State {
     pub network: Option<Network>,
@@ -32,6 +23,25 @@ pub struct State {
     #[serde(skip_deserializing)]
     pub address: Option<Address>,
     pub ip: Option<IpAddr>,
+    /// Parity image pinned by operator instead of the one from setup templates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parity_image: Option<String>,
+    /// Host ports of the node, default ones if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ports: Option<NodePorts>,
+    /// Phases of the unfinished setup run, the next run resumes after them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<RunProgress>,
+}
+
+/// Progress of the setup run, see [`crate::phases::pipeline::Pipeline`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunProgress {
+    pub completed: Vec<String>,
+    /// Phase which has failed, `None` if the run has been interrupted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed: Option<String>,
 }
 
 impl JsonConfig for State {
@@ -41,14 +51,15 @@ impl JsonConfig for State {
 
 impl State {
     pub fn path() -> PathBuf {
-        match std::env::var("STORE_PATH").as_deref() {
-            Ok(path) => PathBuf::from(&ConfigPath::Absolute { path }),
-            Err(_) => PathBuf::from(&DEFAULT_STATE_PATH),
-        }
+        profile::active().state_path()
     }
 
     pub fn read() -> Result<Self, AppError> {
-        let res = Self::load_json(Self::path());
+        Self::read_from(Self::path())
+    }
+
+    pub fn read_from(path: PathBuf) -> Result<Self, AppError> {
+        let res = Self::load_json(path);
 
         if matches!(&res, Err(ConfigError::Foreign(e))
             if e.downcast_ref::<std::io::Error>().map(|e| e.kind())
@@ -67,13 +78,20 @@ impl State {
         .map_err(AppError::from)
     }
 
-    pub fn write(&self) -> Result<(), AppError> {
-        let file = File::create(Self::path())?;
-        let mut writer = BufWriter::new(file);
+    /// Writes the state atomically and readable by the owner only, since it holds the private key
+    pub async fn write(&self) -> Result<(), AppError> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
 
-        serde_json::to_writer_pretty(&mut writer, &self)?;
+        utils::write_file_atomic(&path, serde_json::to_vec_pretty(&self)?)
+            .await
+            .map_err(AppError::from)
+    }
 
-        writer.flush().map_err(AppError::from)
+    pub fn ports(&self) -> NodePorts {
+        self.ports.unwrap_or_default()
     }
 
     pub fn is_complete(&self) -> bool {
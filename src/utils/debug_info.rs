@@ -1,25 +1,47 @@
 use alloy::primitives::Address;
 use chrono::Utc;
-use std::{fmt::write, path::PathBuf};
+use flate2::{write::GzEncoder, Compression};
+use serde::Serialize;
+use std::path::PathBuf;
 
-use super::exec;
+use super::{
+    exec,
+    redact::Redactor,
+    sysinfo::{self, ContainerStats, DiskUsage, LoadAverage, MemoryInfo, OsRelease, ProcessInfo},
+};
 use crate::{config::Network, error::AppError, state::State};
 
+const REPORT_FILE_NAME: &str = "report.json";
+const COMPOSE_LOGS_FILE_NAME: &str = "docker-compose.log";
+/// Output directory files attached to the report. Password and keyfile are never attached
+const ATTACHED_OUTPUT_FILES: [&str; 3] = [
+    "docker-compose.yml",
+    "parity_config.toml",
+    "update_history.json",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DebugInfo {
     network: Option<Network>,
     pub address: Address,
     pub timestamp: i64,
     cwd: Option<PathBuf>,
-    os_release: String,
-    memory_info: String,
+    pub os_release: Option<OsRelease>,
+    pub memory: Option<MemoryInfo>,
+    pub load_average: Option<LoadAverage>,
+    pub uptime_secs: Option<u64>,
+    /// Usage of the filesystem containing output directory
+    pub disk: Option<DiskUsage>,
+    pub processes: Vec<ProcessInfo>,
+    pub containers: Vec<ContainerStats>,
     directory_contents: String,
     output_directory_contents: String,
-    disk_block_info: String,
-    disk_inodes_info: String,
-    process_tree: String,
-    memory_usage: String,
-    compose_logs: String,
     build: Option<String>,
+    #[serde(skip)]
+    compose_logs: String,
+    #[serde(skip)]
+    output_files: Vec<(String, String)>,
 }
 
 impl DebugInfo {
@@ -30,7 +52,13 @@ impl DebugInfo {
             ..
         } = State::read()?;
 
-        // let (local_head, remote_head) = exec::get_git_commits().await;
+        let output_dir = super::output_dir();
+        let mut output_files = Vec::with_capacity(ATTACHED_OUTPUT_FILES.len());
+        for file_name in ATTACHED_OUTPUT_FILES {
+            if let Ok(content) = tokio::fs::read_to_string(output_dir.join(file_name)).await {
+                output_files.push((file_name.to_owned(), content));
+            }
+        }
 
         Ok(Self {
             network,
@@ -40,16 +68,18 @@ impl DebugInfo {
                 .unwrap_or_default(),
             timestamp: Utc::now().timestamp(),
             cwd: std::env::current_dir().ok(),
-            os_release: exec::get_os_release(),
-            memory_info: exec::get_mem_info(),
+            os_release: sysinfo::os_release().ok(),
+            memory: sysinfo::memory_info().ok(),
+            load_average: sysinfo::load_average().ok(),
+            uptime_secs: sysinfo::uptime().ok().map(|uptime| uptime.as_secs()),
+            disk: sysinfo::disk_usage(&output_dir).ok(),
+            processes: sysinfo::processes().unwrap_or_default(),
+            containers: sysinfo::container_stats(),
             directory_contents: exec::get_directory_contents(None),
-            output_directory_contents: exec::get_directory_contents(Some(super::output_dir())),
-            disk_block_info: exec::get_disk_block_info(),
-            disk_inodes_info: exec::get_disk_inodes_info(),
-            process_tree: exec::get_process_tree(),
-            memory_usage: exec::get_memory_usage(),
+            output_directory_contents: exec::get_directory_contents(Some(output_dir)),
             compose_logs: exec::get_docker_compose_logs(),
-            build: std::env::var("CARGO_PKG_VERSION").ok(),
+            build: option_env!("CARGO_PKG_VERSION").map(str::to_owned),
+            output_files,
         })
     }
 
@@ -59,50 +89,67 @@ impl DebugInfo {
             .map(|network| network.name.as_str())
             .unwrap_or_default()
     }
-}
 
-impl std::fmt::Debug for DebugInfo {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write(
-            f,
-            format_args!(
-                "
-                    Address: {:?}
-                    Network: {}
-                    Timestamp: {}
-
-                    Network details: {}
-                    Working directory: {:?}
-                    OS Release: {}
-                    Memory Info: {}
-                    Directory Contents: {}
-                    Output Directory Contents: {}
-                    Disk Block Info: {}
-                    Disk Inodes Info: {}
-                    Process Tree: {}
-                    Memory Usage: {}
-                    Docker logs: {}
-                    Build: {}
-                ",
-                self.address,
-                self.network_name(),
-                self.timestamp,
-                self.network
-                    .as_ref()
-                    .and_then(|network| serde_json::to_string_pretty(network).ok())
-                    .unwrap_or_default(),
-                self.cwd.clone().unwrap_or_default(),
-                self.os_release,
-                self.memory_info,
-                self.directory_contents,
-                self.output_directory_contents,
-                self.disk_block_info,
-                self.disk_inodes_info,
-                self.process_tree,
-                self.memory_usage,
-                self.compose_logs,
-                self.build.as_deref().unwrap_or_default(),
-            ),
-        )
+    /// Replaces secrets in every collected text field
+    pub fn redact(&mut self, redactor: &Redactor) {
+        for field in [
+            &mut self.directory_contents,
+            &mut self.output_directory_contents,
+            &mut self.compose_logs,
+        ] {
+            *field = redactor.redact(field);
+        }
+
+        for (_, content) in &mut self.output_files {
+            *content = redactor.redact(content);
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("{:?}-{}", self.address, self.timestamp)
+    }
+
+    pub fn to_json(&self) -> Result<String, AppError> {
+        serde_json::to_string_pretty(self).map_err(AppError::from)
+    }
+
+    /// Names and sizes of the files which will be packed into the report bundle
+    pub fn bundle_contents(&self) -> Result<Vec<(String, usize)>, AppError> {
+        Ok(self
+            .bundle_files()?
+            .into_iter()
+            .map(|(name, content)| (name, content.len()))
+            .collect())
+    }
+
+    /// Packs JSON report, compose logs and configuration files into a `.tar.gz` archive
+    pub fn to_tarball(&self) -> Result<Vec<u8>, AppError> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        for (name, content) in self.bundle_files()? {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(self.timestamp.max(0) as u64);
+            header.set_cksum();
+
+            builder.append_data(
+                &mut header,
+                format!("{}/{name}", self.title()),
+                content.as_bytes(),
+            )?;
+        }
+
+        builder.into_inner()?.finish().map_err(AppError::from)
+    }
+
+    fn bundle_files(&self) -> Result<Vec<(String, String)>, AppError> {
+        Ok([
+            (REPORT_FILE_NAME.to_owned(), self.to_json()?),
+            (COMPOSE_LOGS_FILE_NAME.to_owned(), self.compose_logs.clone()),
+        ]
+        .into_iter()
+        .chain(self.output_files.iter().cloned())
+        .collect())
     }
 }
@@ -0,0 +1,156 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::messages::MessageType;
+
+/// Known OpenEthereum failure signatures found in `docker-compose logs` output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issue {
+    DatabaseCorruption,
+    EngineSignerPassword,
+    NatMisconfiguration,
+    OutOfDisk,
+    ClockDrift,
+}
+
+/// Fixes which NOP is able to apply without operator's manual work
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutomatedFix {
+    /// Remove chain database and restore it from backup
+    RestoreChainFromBackup,
+    /// Regenerate keystore password and keyfile and restart containers
+    RegenerateKeystore,
+}
+
+struct Rule {
+    issue: Issue,
+    pattern: Regex,
+    fix: Option<AutomatedFix>,
+}
+
+static RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        Rule {
+            issue: Issue::DatabaseCorruption,
+            pattern: Regex::new(
+                r"(?i)corruption:|database corrupted|marked as corrupted|error opening database|Client\(Database\(",
+            )
+            .unwrap(),
+            fix: Some(AutomatedFix::RestoreChainFromBackup),
+        },
+        Rule {
+            issue: Issue::EngineSignerPassword,
+            pattern: Regex::new(
+                r"(?i)invalid password|unable to unlock|signer account not unlocked|no validator key",
+            )
+            .unwrap(),
+            fix: Some(AutomatedFix::RegenerateKeystore),
+        },
+        Rule {
+            issue: Issue::NatMisconfiguration,
+            pattern: Regex::new(
+                r"(?i)invalid nat|failed to parse nat|Public node URL: enode://[0-9a-f]+@(0\.0\.0\.0|127\.|10\.|192\.168\.|172\.(1[6-9]|2\d|3[01])\.)",
+            )
+            .unwrap(),
+            fix: None,
+        },
+        Rule {
+            issue: Issue::OutOfDisk,
+            pattern: Regex::new(r"(?i)no space left on device|disk (is )?full").unwrap(),
+            fix: None,
+        },
+        Rule {
+            issue: Issue::ClockDrift,
+            pattern: Regex::new(
+                r"(?i)temporarilyinvalid|block from (the )?future|clock (drift|skew)|system time",
+            )
+            .unwrap(),
+            fix: None,
+        },
+    ]
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub issue: Issue,
+    pub occurrences: usize,
+    /// Last log line matching the issue signature
+    pub sample: String,
+    pub fix: Option<AutomatedFix>,
+}
+
+impl Issue {
+    pub fn explanation(&self) -> MessageType<'static> {
+        match self {
+            Issue::DatabaseCorruption => MessageType::DiagnosisDatabaseCorruption,
+            Issue::EngineSignerPassword => MessageType::DiagnosisEngineSignerPassword,
+            Issue::NatMisconfiguration => MessageType::DiagnosisNatMisconfiguration,
+            Issue::OutOfDisk => MessageType::DiagnosisOutOfDisk,
+            Issue::ClockDrift => MessageType::DiagnosisClockDrift,
+        }
+    }
+}
+
+/// Scans logs for known failure signatures. Findings are returned in rules order
+pub fn diagnose(logs: &str) -> Vec<Finding> {
+    RULES
+        .iter()
+        .filter_map(|rule| {
+            let matched = logs
+                .lines()
+                .filter(|line| rule.pattern.is_match(line))
+                .collect::<Vec<_>>();
+
+            Some(Finding {
+                issue: rule.issue,
+                occurrences: matched.len(),
+                sample: matched.last()?.trim().to_owned(),
+                fix: rule.fix,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose() {
+        let logs = "\
+parity  | 2024-11-01 10:00:00  IO Worker #1 INFO import  Imported #1234 0xab12…cd34 (0 txs, 0.00 Mgas, 1 ms, 0.57 KiB)
+parity  | 2024-11-01 10:00:01  Verifier #0 ERROR client  Corruption: block checksum mismatch
+parity  | 2024-11-01 10:00:02  main INFO parity_ethcore  Public node URL: enode://ab12cd@192.168.1.10:30303
+parity  | 2024-11-01 10:00:03  Verifier #0 ERROR client  Corruption: bad block contents
+parity  | 2024-11-01 10:00:04  main WARN ethcore::engines  Signing: Invalid password for account";
+
+        let findings = diagnose(logs);
+        assert_eq!(
+            findings.iter().map(|f| f.issue).collect::<Vec<_>>(),
+            vec![
+                Issue::DatabaseCorruption,
+                Issue::EngineSignerPassword,
+                Issue::NatMisconfiguration
+            ]
+        );
+        assert_eq!(findings[0].occurrences, 2);
+        assert!(findings[0]
+            .sample
+            .ends_with("Corruption: bad block contents"));
+        assert_eq!(findings[0].fix, Some(AutomatedFix::RestoreChainFromBackup));
+        assert_eq!(findings[2].fix, None);
+
+        assert!(diagnose(
+            "parity  | 2024-11-01 10:00:02  main INFO parity_ethcore  Public node URL: enode://ab12cd@34.12.1.10:30303"
+        )
+        .is_empty());
+        assert_eq!(
+            diagnose("parity  | IO error: No space left on device")[0].issue,
+            Issue::OutOfDisk
+        );
+        assert_eq!(
+            diagnose("parity  | Rejected block: TemporarilyInvalid(OutOfBounds)")[0].issue,
+            Issue::ClockDrift
+        );
+    }
+}
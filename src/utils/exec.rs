@@ -1,5 +1,4 @@
 use anyhow::anyhow;
-use futures::FutureExt;
 use regex::Regex;
 use std::{
     path::PathBuf,
@@ -7,7 +6,24 @@ use std::{
     str,
 };
 
-use crate::{error::AppError, utils};
+use crate::{
+    error::AppError,
+    utils::{
+        self,
+        logs::{LogFilter, PARITY_SERVICE_NAME},
+        profile,
+    },
+};
+
+/// `docker-compose` running in output directory within the project of the active profile
+fn docker_compose() -> Command {
+    let mut cmd = Command::new("docker-compose");
+    cmd.current_dir(utils::output_dir());
+    if let Some(project_name) = profile::active().project_name() {
+        cmd.arg("--project-name").arg(project_name);
+    }
+    cmd
+}
 
 pub fn is_docker_installed() -> Result<bool, AppError> {
     let docker_version_regexp =
@@ -20,10 +36,7 @@ pub fn is_docker_installed() -> Result<bool, AppError> {
 }
 
 pub fn run_docker_compose_up() -> Result<(), AppError> {
-    let output_dir = utils::output_dir();
-
-    match Command::new("docker-compose")
-        .current_dir(output_dir)
+    match docker_compose()
         .arg("--compatibility")
         .arg("up")
         .arg("-d")
@@ -40,13 +53,7 @@ pub fn run_docker_compose_up() -> Result<(), AppError> {
 }
 
 pub fn run_docker_compose_down() -> Result<(), AppError> {
-    let output_dir = utils::output_dir();
-
-    match Command::new("docker-compose")
-        .current_dir(output_dir)
-        .arg("down")
-        .output()?
-    {
+    match docker_compose().arg("down").output()? {
         Output { status, .. } if status.success() => Ok(()),
         Output { status, stderr, .. } => Err(anyhow!(
             "Run `docker-compose down` failure ({:?}). Error: {:?}",
@@ -58,13 +65,7 @@ pub fn run_docker_compose_down() -> Result<(), AppError> {
 }
 
 pub fn run_docker_compose_pull() -> Result<(), AppError> {
-    let output_dir = utils::output_dir();
-
-    match Command::new("docker-compose")
-        .current_dir(output_dir)
-        .arg("pull")
-        .output()?
-    {
+    match docker_compose().arg("pull").output()? {
         Output { status, .. } if status.success() => Ok(()),
         Output { status, stderr, .. } => Err(anyhow!(
             "Run `docker-compose pull` failure ({:?}). Error: {:?}",
@@ -75,6 +76,18 @@ pub fn run_docker_compose_pull() -> Result<(), AppError> {
     }
 }
 
+pub fn run_docker_compose_restart(service: &str) -> Result<(), AppError> {
+    match docker_compose().arg("restart").arg(service).output()? {
+        Output { status, .. } if status.success() => Ok(()),
+        Output { status, stderr, .. } => Err(anyhow!(
+            "Run `docker-compose restart {service}` failure ({:?}). Error: {:?}",
+            status.code(),
+            std::str::from_utf8(&stderr)
+        )
+        .into()),
+    }
+}
+
 pub async fn run_download_backup(url: &str) -> Result<(), AppError> {
     let output_dir = utils::output_dir();
 
@@ -106,18 +119,6 @@ pub fn docker_compose_restart() -> Result<(), AppError> {
     run_docker_compose_up()
 }
 
-pub async fn run_update(update_file: PathBuf) -> Result<(), AppError> {
-    match tokio::process::Command::new(update_file).output().await? {
-        Output { status, .. } if status.success() => Ok(()),
-        Output { status, stderr, .. } => Err(anyhow!(
-            "Run `./update.sh` failure ({:?}). Error: {:?}",
-            status.code(),
-            std::str::from_utf8(&stderr)
-        )
-        .into()),
-    }
-}
-
 // pub async fn get_parity_container_ip() -> Result<IpAddr, AppError> {
 //     match tokio::process::Command::new("docker")
 //         .arg("inspect")
@@ -139,14 +140,6 @@ pub async fn run_update(update_file: PathBuf) -> Result<(), AppError> {
 //     }
 // }
 
-pub fn get_os_release() -> String {
-    super::output_into_string(Command::new("cat").arg("/etc/os-release").output())
-}
-
-pub fn get_mem_info() -> String {
-    super::output_into_string(Command::new("cat").arg("/proc/meminfo").output())
-}
-
 pub fn get_directory_contents(cwd: Option<PathBuf>) -> String {
     let mut cmd = Command::new("ls");
 
@@ -157,79 +150,46 @@ pub fn get_directory_contents(cwd: Option<PathBuf>) -> String {
     super::output_into_string(cmd.arg("-la").output())
 }
 
-pub fn get_disk_block_info() -> String {
-    super::output_into_string(Command::new("df").arg("-h").output())
-}
-
-pub fn get_disk_inodes_info() -> String {
-    super::output_into_string(Command::new("df").arg("-i").output())
-}
-
-pub fn get_process_tree() -> String {
-    super::output_into_string(Command::new("ps").arg("axjf").output())
+pub fn get_docker_compose_logs() -> String {
+    super::output_into_string(docker_compose().arg("logs").arg("--tail=500").output())
 }
 
-pub fn get_memory_usage() -> String {
-    super::output_into_string(Command::new("free").arg("-m").output())
-}
+pub fn spawn_docker_compose_logs(filter: &LogFilter) -> Result<tokio::process::Child, AppError> {
+    let mut cmd = tokio::process::Command::from(docker_compose());
+    cmd.arg("logs").arg("--no-color");
 
-pub fn get_docker_compose_logs() -> String {
-    super::output_into_string(
-        Command::new("docker-compose")
-            .current_dir(super::output_dir())
-            .arg("logs")
-            .arg("--tail=500")
-            .output(),
-    )
-}
+    if filter.follow {
+        cmd.arg("--follow");
+    }
+    if let Some(tail) = filter.tail {
+        cmd.arg(format!("--tail={tail}"));
+    }
+    if let Some(since) = &filter.since {
+        cmd.arg(format!("--since={since}"));
+    }
+    if let Some(until) = &filter.until {
+        cmd.arg(format!("--until={until}"));
+    }
 
-#[allow(unused)]
-pub async fn get_git_commits() -> (String, String) {
-    if let Ok(mut child) = tokio::process::Command::new("git")
-        .arg("fetch")
-        .arg("origin")
+    cmd.args(&filter.services)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
         .spawn()
-    {
-        let _ = child.wait().await;
-    }
-
-    let local_head = super::output_into_string(
-        tokio::process::Command::new("git")
-            .arg("rev-parse")
-            .arg("HEAD")
-            .output()
-            .await,
-    );
+        .map_err(AppError::from)
+}
 
-    // Try to acquire active branch name and then remote head commit for that branch
-    match tokio::process::Command::new("git")
-        .arg("rev-parse")
-        .arg("--abbrev-ref")
-        .arg("HEAD")
+pub fn get_container_status(name: &str) -> Option<String> {
+    match Command::new("docker")
+        .arg("inspect")
+        .arg("--format={{ .State.Status }}")
+        .arg(name)
         .output()
-        .then(|branch_res| async move {
-            let branch = match branch_res.as_ref() {
-                Ok(Output { status, stdout, .. }) if status.success() => {
-                    std::str::from_utf8(stdout)
-                        .map_err(|e| format!("Format error: {e:?}. Raw: {stdout:?}"))
-                }
-                _ => Err(super::output_into_string(branch_res)),
-            }?;
-
-            Ok(super::output_into_string(
-                tokio::process::Command::new("git")
-                    .arg("rev-parse")
-                    .arg(["origin/", branch.trim()].concat())
-                    .output()
-                    .await,
-            ))
-        })
-        .await
     {
-        Ok(remote_head) => (local_head, remote_head),
-        Err(err) => (local_head, err),
+        Ok(Output { status, stdout, .. }) if status.success() => std::str::from_utf8(&stdout)
+            .ok()
+            .map(|status| status.trim().to_owned()),
+        _ => None,
     }
 }
 
@@ -238,14 +198,14 @@ pub fn get_node_version() -> String {
         .stdin(std::process::Stdio::piped())
         .arg("inspect")
         .arg("--format='{{ index .Config.Image }}'")
-        .arg("parity")
+        .arg(profile::active().container_name(PARITY_SERVICE_NAME))
         .output();
 
     let success = matches!(&res, Ok(Output { status, .. }) if status.success());
     let output = super::output_into_string(res);
 
     if success {
-        let Some((_, version)) = output.trim().trim_matches('\'').split_once(':') else {
+        let Some(version) = utils::image_tag(output.trim().trim_matches('\'')) else {
             return output;
         };
 
@@ -0,0 +1,240 @@
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{error::AppError, messages::MessageType};
+
+/// Node is considered lagging if it's behind the network by more blocks than this
+pub const MAX_BLOCK_LAG: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FleetFormat {
+    Table,
+    Json,
+}
+
+/// Nodes operated on other hosts, e.g.
+/// `{ "network": "main", "nodes": [{ "name": "apollo-1", "address": "0x..", "rpc": "http://10.0.0.1:8545" }] }`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetInventory {
+    /// Network name from NOP config
+    pub network: String,
+    pub nodes: Vec<FleetNode>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetNode {
+    /// Short label, address is shown if not set
+    #[serde(default)]
+    pub name: Option<String>,
+    pub address: Address,
+    /// Node RPC reachable from this host, health isn't checked without it
+    #[serde(default)]
+    pub rpc: Option<reqwest::Url>,
+    /// Metrics endpoint which must respond with success status
+    #[serde(default)]
+    pub metrics: Option<reqwest::Url>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Registration {
+    NotRegistered,
+    Onboarding,
+    Onboarded,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetHealth {
+    pub block_number: Option<u64>,
+    /// Blocks behind the network
+    pub block_lag: Option<u64>,
+    pub peers_count: Option<u64>,
+    pub syncing: Option<bool>,
+    pub metrics_up: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetNodeReport {
+    pub name: String,
+    pub address: Address,
+    pub registration: Option<Registration>,
+    /// Stake in AMB
+    pub stake: Option<String>,
+    pub onboarding_wait_secs: Option<u64>,
+    /// Missing if the node has no endpoints in the inventory
+    pub health: Option<FleetHealth>,
+    pub errors: Vec<String>,
+}
+
+impl FleetInventory {
+    pub async fn read(path: &Path) -> Result<Self, AppError> {
+        let content = tokio::fs::read(path).await?;
+        serde_json::from_slice(&content).map_err(AppError::from)
+    }
+}
+
+impl FleetNode {
+    pub fn label(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.address.to_string())
+    }
+}
+
+impl FleetHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.block_number.is_some()
+            && !matches!(self.block_lag, Some(lag) if lag > MAX_BLOCK_LAG)
+            && !matches!(self.peers_count, Some(0))
+            && self.syncing != Some(true)
+            && self.metrics_up != Some(false)
+    }
+}
+
+impl FleetNodeReport {
+    pub fn new(node: &FleetNode) -> Self {
+        Self {
+            name: node.label(),
+            address: node.address,
+            registration: None,
+            stake: None,
+            onboarding_wait_secs: None,
+            health: None,
+            errors: Vec::new(),
+        }
+    }
+
+    fn registration_cell(&self) -> String {
+        match (self.registration, self.onboarding_wait_secs) {
+            (Some(Registration::Onboarding), Some(secs)) => {
+                MessageType::FleetOnboardingLeft { secs }
+            }
+            (Some(Registration::Onboarding), None) => MessageType::FleetOnboarding,
+            (Some(Registration::Onboarded), _) => MessageType::FleetOnboarded,
+            (Some(Registration::NotRegistered), _) => MessageType::FleetNotRegistered,
+            (None, _) => MessageType::StatusUnknown,
+        }
+        .to_string()
+    }
+
+    fn health_cell(&self) -> String {
+        let Some(health) = &self.health else {
+            return "-".to_owned();
+        };
+
+        let mut details = Vec::new();
+        if let Some(block_number) = health.block_number {
+            details.push(format!("#{block_number}"));
+        }
+        if let Some(lag) = health.block_lag {
+            details.push(MessageType::FleetLag { lag }.to_string());
+        }
+        if let Some(peers) = health.peers_count {
+            details.push(MessageType::FleetPeers { peers }.to_string());
+        }
+        if health.syncing == Some(true) {
+            details.push(MessageType::FleetSyncing.to_string());
+        }
+        if health.metrics_up == Some(false) {
+            details.push(MessageType::FleetMetricsDown.to_string());
+        }
+
+        let mark = if health.is_healthy() { "✅" } else { "⛔" };
+        format!("{mark} {}", details.join(", "))
+    }
+}
+
+/// Renders reports as a table, one node per line followed by collected errors
+pub fn render_table(reports: &[FleetNodeReport]) -> String {
+    let name_column = MessageType::FleetColumnNode.to_string();
+    let name_width = reports
+        .iter()
+        .map(|report| report.name.chars().count())
+        .chain([name_column.chars().count()])
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![format!(
+        "{name_column:<name_width$}  {:<24}  {:>16}  {}",
+        MessageType::FleetColumnRegistration.to_string(),
+        MessageType::FleetColumnStake.to_string(),
+        MessageType::FleetColumnHealth,
+    )];
+    lines.extend(reports.iter().map(|report| {
+        format!(
+            "{:<name_width$}  {:<24}  {:>16}  {}",
+            report.name,
+            report.registration_cell(),
+            report.stake.as_deref().unwrap_or("-"),
+            report.health_cell(),
+        )
+    }));
+
+    let errors = reports
+        .iter()
+        .flat_map(|report| {
+            report
+                .errors
+                .iter()
+                .map(move |error| format!("{}: {error}", report.name))
+        })
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        lines.push(format!("\n{}", MessageType::FleetErrors));
+        lines.extend(errors);
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fleet_report() {
+        let inventory: FleetInventory = serde_json::from_value(json!({
+            "network": "main",
+            "nodes": [
+                { "name": "apollo-1", "address": "0x0000000000000000000000000000000000000001", "rpc": "http://10.0.0.1:8545" },
+                { "address": "0x0000000000000000000000000000000000000002" },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(inventory.nodes.len(), 2);
+        assert!(inventory.nodes[1].rpc.is_none());
+
+        let mut onboarded = FleetNodeReport::new(&inventory.nodes[0]);
+        onboarded.registration = Some(Registration::Onboarded);
+        onboarded.stake = Some("1000.0".to_owned());
+        onboarded.health = Some(FleetHealth {
+            block_number: Some(100),
+            block_lag: Some(1),
+            peers_count: Some(5),
+            syncing: Some(false),
+            metrics_up: None,
+        });
+
+        let mut onboarding = FleetNodeReport::new(&inventory.nodes[1]);
+        onboarding.registration = Some(Registration::Onboarding);
+        onboarding.onboarding_wait_secs = Some(60);
+        onboarding.errors.push("rpc timeout".to_owned());
+
+        assert_eq!(
+            serde_json::to_value(&onboarding).unwrap()["registration"],
+            json!("onboarding")
+        );
+
+        let table = render_table(&[onboarded, onboarding]);
+        assert!(table.contains("apollo-1"));
+        assert!(table.contains("✅ #100, lag 1, 5 peers"));
+        assert!(table.contains("onboarding (60s left)"));
+        assert!(table.contains("0x0000000000000000000000000000000000000002: rpc timeout"));
+    }
+}
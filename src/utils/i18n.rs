@@ -0,0 +1,180 @@
+use anyhow::anyhow;
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, OnceLock},
+};
+
+use crate::error::AppError;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Message catalogs shipped with the binary, by locale
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../../locales/en.toml")),
+    ("es", include_str!("../../locales/es.toml")),
+];
+
+static PARSED_CATALOGS: LazyLock<HashMap<&'static str, HashMap<String, String>>> =
+    LazyLock::new(|| {
+        CATALOGS
+            .iter()
+            .map(|(locale, content)| {
+                let catalog = toml::from_str(content)
+                    .unwrap_or_else(|e| panic!("Invalid `{locale}` message catalog: {e}"));
+                (*locale, catalog)
+            })
+            .collect()
+    });
+
+static ACTIVE_LOCALE: OnceLock<&'static str> = OnceLock::new();
+
+/// Shipped locale matching the language, accepts `LANG` values like `es_ES.UTF-8`
+pub fn locale(lang: &str) -> Option<&'static str> {
+    let language = lang
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    CATALOGS
+        .iter()
+        .map(|(locale, _)| *locale)
+        .find(|locale| *locale == language)
+}
+
+/// Selects locale by `--lang` value, falling back to `LANG` environment variable. Unsupported
+/// `LANG` means the default locale, while unsupported `--lang` is an error
+pub fn init(lang: Option<&str>) -> Result<(), AppError> {
+    let locale = match lang {
+        Some(lang) => locale(lang).ok_or_else(|| {
+            anyhow!(
+                "Unsupported language `{lang}`, available: {}",
+                CATALOGS.map(|(locale, _)| locale).join(", ")
+            )
+        })?,
+        None => std::env::var("LANG")
+            .ok()
+            .as_deref()
+            .and_then(locale)
+            .unwrap_or(DEFAULT_LOCALE),
+    };
+
+    let _ = ACTIVE_LOCALE.set(locale);
+    Ok(())
+}
+
+pub fn active() -> &'static str {
+    ACTIVE_LOCALE.get().copied().unwrap_or(DEFAULT_LOCALE)
+}
+
+/// Message text in the active locale, English one if it's not translated
+pub fn message(key: &'static str) -> &'static str {
+    [active(), DEFAULT_LOCALE]
+        .into_iter()
+        .find_map(|locale| PARSED_CATALOGS.get(locale)?.get(key))
+        .map(String::as_str)
+        .unwrap_or(key)
+}
+
+/// Replaces `{name}` placeholders with the values, unknown placeholders are kept as is
+pub fn render(template: &str, args: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            args.iter()
+                .find(|(name, _)| *name == &rest[1..end])
+                .map(|(_, value)| (value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageKey;
+    use regex::Regex;
+    use std::collections::BTreeSet;
+    use strum::IntoEnumIterator;
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        let regex = Regex::new(r"\{(\w+)\}").unwrap();
+        regex
+            .captures_iter(text)
+            .map(|captures| captures.get(1).unwrap().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_complete() {
+        let keys = MessageKey::iter()
+            .map(<&'static str>::from)
+            .collect::<BTreeSet<_>>();
+        let english = &PARSED_CATALOGS[DEFAULT_LOCALE];
+
+        for (locale, _) in CATALOGS {
+            let catalog = &PARSED_CATALOGS[locale];
+            for key in &keys {
+                let Some(text) = catalog.get(*key) else {
+                    panic!("`{key}` is not translated into `{locale}`");
+                };
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(&english[*key]),
+                    "`{key}` placeholders differ in `{locale}`"
+                );
+            }
+
+            let unknown = catalog
+                .keys()
+                .filter(|key| !keys.contains(key.as_str()))
+                .collect::<Vec<_>>();
+            assert!(
+                unknown.is_empty(),
+                "Unknown keys in `{locale}`: {unknown:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_locale() {
+        assert_eq!(locale("es_ES.UTF-8"), Some("es"));
+        assert_eq!(locale("en-US"), Some("en"));
+        assert_eq!(locale("ES"), Some("es"));
+        assert_eq!(locale("C"), None);
+        assert_eq!(locale("fr_FR.UTF-8"), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let args = [
+            ("ip", "203.0.113.7".to_owned()),
+            ("port", "30303".to_owned()),
+        ];
+        assert_eq!(
+            render("{ip}:{port} {unknown} {", &args),
+            "203.0.113.7:30303 {unknown} {"
+        );
+        assert_eq!(
+            crate::messages::MessageType::P2pNoPeers { port: 30303 }.to_string(),
+            render(message("P2pNoPeers"), &[("port", "30303".to_owned())])
+        );
+    }
+}
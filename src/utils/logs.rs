@@ -0,0 +1,259 @@
+use console::style;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+/// Docker compose service names of the node containers
+pub const PARITY_SERVICE_NAME: &str = "parity";
+pub const ETHSTATS_SERVICE_NAME: &str = "ethstats-client";
+
+/// Time without `Imported #` messages after which block import is considered stalled
+const IMPORT_STALL_THRESHOLD: Duration = Duration::from_secs(300);
+
+static BAD_BLOCK_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)bad block|block import failed|rejected block").unwrap());
+static PEER_DROP_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)disconnect(ed|ing) peer|removed peer|\s0/\d+ peers").unwrap()
+});
+static IMPORTED_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Imported #\d+").unwrap());
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl FromStr for LogLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TRACE" => Ok(Self::Trace),
+            "DEBUG" => Ok(Self::Debug),
+            "INFO" => Ok(Self::Info),
+            "WARN" => Ok(Self::Warn),
+            "ERROR" => Ok(Self::Error),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Known OpenEthereum log patterns worth operator attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogHighlight {
+    BadBlock,
+    PeerDrop,
+    Imported,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub services: Vec<String>,
+    pub level: Option<LogLevel>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub search: Option<Regex>,
+    pub follow: bool,
+    pub tail: Option<usize>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, line: &LogLine) -> bool {
+        if let Some(min_level) = self.level {
+            // Lines without explicit level (e.g. ethstats client output) are treated as `INFO`
+            if line.level.unwrap_or(LogLevel::Info) < min_level {
+                return false;
+            }
+        }
+
+        match &self.search {
+            Some(regex) => regex.is_match(&line.text),
+            None => true,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub service: Option<String>,
+    pub text: String,
+    pub level: Option<LogLevel>,
+}
+
+impl LogLine {
+    /// Parses a single line of `docker-compose logs` output, e.g. `parity  | 2024-01-01 ... INFO import ...`
+    pub fn parse(raw: &str) -> Self {
+        let (service, text) = match raw.split_once(" | ") {
+            Some((service, text)) if !service.trim().contains(' ') => {
+                (Some(service.trim().to_owned()), text)
+            }
+            _ => (None, raw),
+        };
+
+        Self {
+            service,
+            level: text
+                .split_whitespace()
+                .find_map(|word| LogLevel::from_str(word).ok()),
+            text: text.to_owned(),
+        }
+    }
+
+    pub fn highlight(&self) -> Option<LogHighlight> {
+        if BAD_BLOCK_REGEX.is_match(&self.text) {
+            Some(LogHighlight::BadBlock)
+        } else if PEER_DROP_REGEX.is_match(&self.text) {
+            Some(LogHighlight::PeerDrop)
+        } else if IMPORTED_REGEX.is_match(&self.text) {
+            Some(LogHighlight::Imported)
+        } else {
+            None
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let prefix = self
+            .service
+            .as_deref()
+            .map(|service| format!("{} | ", style(service).cyan()))
+            .unwrap_or_default();
+
+        let text = match (self.highlight(), self.level) {
+            (Some(LogHighlight::BadBlock), _) | (_, Some(LogLevel::Error)) => {
+                style(&self.text).red().bold().to_string()
+            }
+            (Some(LogHighlight::PeerDrop), _) | (_, Some(LogLevel::Warn)) => {
+                style(&self.text).yellow().to_string()
+            }
+            (Some(LogHighlight::Imported), _) => style(&self.text).green().to_string(),
+            _ => self.text.clone(),
+        };
+
+        [prefix, text].concat()
+    }
+}
+
+/// Tracks `Imported #` messages to detect block import stalls while following logs
+pub struct ImportStallDetector {
+    last_import: Instant,
+    threshold: Duration,
+    reported: bool,
+}
+
+impl Default for ImportStallDetector {
+    fn default() -> Self {
+        Self {
+            last_import: Instant::now(),
+            threshold: IMPORT_STALL_THRESHOLD,
+            reported: false,
+        }
+    }
+}
+
+impl ImportStallDetector {
+    /// Records block import if the line reports it
+    pub fn observe(&mut self, line: &LogLine) {
+        if line.highlight() == Some(LogHighlight::Imported) {
+            self.last_import = Instant::now();
+            self.reported = false;
+        }
+    }
+
+    /// Returns the duration of the stall once it exceeds the threshold, once per stall.
+    /// Called periodically as well, since stalled parity often stops logging at all
+    pub fn check(&mut self) -> Option<Duration> {
+        let elapsed = self.last_import.elapsed();
+        if !self.reported && elapsed >= self.threshold {
+            self.reported = true;
+            Some(elapsed)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_line_parse() {
+        let line = LogLine::parse(
+            "parity           | 2024-11-01 10:00:00  IO Worker #1 INFO import  Imported #1234 0xab12…cd34 (0 txs, 0.00 Mgas, 1 ms, 0.57 KiB)",
+        );
+        assert_eq!(line.service.as_deref(), Some("parity"));
+        assert_eq!(line.level, Some(LogLevel::Info));
+        assert_eq!(line.highlight(), Some(LogHighlight::Imported));
+
+        let line = LogLine::parse(
+            "2024-11-01 10:00:00  Verifier #0 WARN client  Bad block detected: Block(InvalidSeal)",
+        );
+        assert_eq!(line.service, None);
+        assert_eq!(line.level, Some(LogLevel::Warn));
+        assert_eq!(line.highlight(), Some(LogHighlight::BadBlock));
+
+        let event = serde_json::to_value(&line).unwrap();
+        assert_eq!(event["level"], serde_json::json!("warn"));
+        assert!(event["service"].is_null());
+
+        let line = LogLine::parse(
+            "2024-11-01 10:00:00  IO Worker #2 INFO import     0/25 peers   1 MiB chain",
+        );
+        assert_eq!(line.highlight(), Some(LogHighlight::PeerDrop));
+    }
+
+    #[test]
+    fn test_import_stall_detector() {
+        let mut detector = ImportStallDetector {
+            threshold: Duration::ZERO,
+            ..Default::default()
+        };
+        assert!(detector.check().is_some());
+        // Reported once per stall
+        assert!(detector.check().is_none());
+        detector.observe(&LogLine::parse(
+            "parity | 2024-11-01 INFO import 3/25 peers",
+        ));
+        assert!(detector.check().is_none());
+
+        detector.observe(&LogLine::parse(
+            "parity | 2024-11-01 INFO import Imported #1",
+        ));
+        assert!(detector.check().is_some());
+
+        let mut detector = ImportStallDetector::default();
+        assert!(detector.check().is_none());
+    }
+
+    #[test]
+    fn test_log_filter_matches() {
+        let filter = LogFilter {
+            level: Some(LogLevel::Warn),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&LogLine::parse(
+            "parity | 2024-11-01 INFO import Imported #1"
+        )));
+        assert!(filter.matches(&LogLine::parse(
+            "parity | 2024-11-01 ERROR client Database corrupted"
+        )));
+        assert!(!filter.matches(&LogLine::parse("ethstats_client | [API] Connected")));
+
+        let filter = LogFilter {
+            search: Some(Regex::new(r"Imported #\d+").unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&LogLine::parse(
+            "parity | 2024-11-01 INFO import Imported #1"
+        )));
+        assert!(!filter.matches(&LogLine::parse("parity | 2024-11-01 INFO import Syncing")));
+    }
+}
@@ -0,0 +1,321 @@
+use alloy::primitives::{keccak256, Address};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{path::PathBuf, time::Duration};
+
+use crate::error::AppError;
+
+const UPDATE_HISTORY_FILE_NAME: &str = "update_history.json";
+/// Older entries are dropped, so the file doesn't grow forever
+const UPDATE_HISTORY_MAX_ENTRIES: usize = 100;
+const DEFAULT_AUTHOR_MARGIN_SECS: u64 = 30;
+const DEFAULT_GRACE_PERIOD_SECS: u64 = 120;
+/// Failed scheduled update isn't retried sooner, so it doesn't restart the node every hour
+const FAILED_UPDATE_RETRY_HOURS: i64 = 24;
+
+/// When and how updates may restart the node
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceConfig {
+    /// Updates run at any time if not set
+    #[serde(default)]
+    pub window: Option<MaintenanceWindow>,
+    /// Upper bound of the per-node delay which spreads restarts of a fleet, seconds
+    #[serde(default)]
+    pub stagger_secs: u64,
+    /// Restart is postponed if the node is due to author a block sooner than this, seconds
+    #[serde(default = "default_author_margin_secs")]
+    pub author_margin_secs: u64,
+    /// Updated node must stay healthy and keep importing blocks for this long, otherwise
+    /// the previous configuration is restored, seconds
+    #[serde(default = "default_grace_period_secs")]
+    pub grace_period_secs: u64,
+}
+
+/// Daily time range in UTC, e.g. `02:00`-`04:00`. May span midnight
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    #[serde(deserialize_with = "de_time")]
+    pub start: NaiveTime,
+    #[serde(deserialize_with = "de_time")]
+    pub end: NaiveTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum UpdateOutcome {
+    Applied,
+    Failed { error: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAttempt {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Started by scheduler rather than by operator
+    pub scheduled: bool,
+    pub nop_version: String,
+    #[serde(flatten)]
+    pub outcome: UpdateOutcome,
+}
+
+/// Update attempts, oldest first
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UpdateHistory {
+    pub attempts: Vec<UpdateAttempt>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            window: None,
+            stagger_secs: 0,
+            author_margin_secs: DEFAULT_AUTHOR_MARGIN_SECS,
+            grace_period_secs: DEFAULT_GRACE_PERIOD_SECS,
+        }
+    }
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Time left until the end of the window, `time` must be within the window
+    pub fn remaining(&self, time: NaiveTime) -> Duration {
+        let remaining = self.end.signed_duration_since(time);
+        let remaining = if remaining < chrono::Duration::zero() {
+            remaining + chrono::Duration::days(1)
+        } else {
+            remaining
+        };
+        remaining.to_std().unwrap_or_default()
+    }
+}
+
+impl UpdateHistory {
+    fn path() -> PathBuf {
+        super::output_dir().join(UPDATE_HISTORY_FILE_NAME)
+    }
+
+    /// Reads history from output directory, missing file means empty history
+    pub async fn read() -> Result<Self, AppError> {
+        match tokio::fs::read(Self::path()).await {
+            Ok(content) => serde_json::from_slice(&content).map_err(AppError::from),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The latest attempt which has applied the update
+    pub fn last_applied(&self) -> Option<&UpdateAttempt> {
+        self.attempts
+            .iter()
+            .rev()
+            .find(|attempt| attempt.outcome == UpdateOutcome::Applied)
+    }
+
+    /// Returns `true` if the latest update by the same NOP version has failed recently,
+    /// so scheduled update must not retry it yet
+    pub fn is_retry_postponed(&self, nop_version: &str, now: DateTime<Utc>) -> bool {
+        self.attempts.last().is_some_and(|attempt| {
+            matches!(attempt.outcome, UpdateOutcome::Failed { .. })
+                && attempt.nop_version == nop_version
+                && now - attempt.finished_at < chrono::Duration::hours(FAILED_UPDATE_RETRY_HOURS)
+        })
+    }
+
+    /// Appends the attempt to the history file
+    pub async fn record(attempt: UpdateAttempt) -> Result<(), AppError> {
+        let mut history = Self::read().await?;
+        history.attempts.push(attempt);
+
+        let overflow = history
+            .attempts
+            .len()
+            .saturating_sub(UPDATE_HISTORY_MAX_ENTRIES);
+        history.attempts.drain(..overflow);
+
+        super::write_file_atomic(&Self::path(), serde_json::to_vec_pretty(&history)?)
+            .await
+            .map_err(AppError::from)
+    }
+}
+
+/// Per-node delay derived from the node address, so a fleet sharing the same window doesn't
+/// restart all at once, while each node keeps the same slot between runs
+pub fn stagger_delay(address: &Address, max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+
+    let hash = keccak256(address);
+    let value = u64::from_be_bytes(hash[..8].try_into().expect("Hash is 32 bytes long"));
+    Duration::from_secs(value % max.as_secs().max(1))
+}
+
+/// Time until the next Aura step authored by `address`, `None` if it's not a validator.
+/// Aura assigns step `n` to validator `n % validators.len()`
+pub fn next_author_step_in(
+    validators: &[Address],
+    address: &Address,
+    step_duration: u64,
+    now: Duration,
+) -> Option<Duration> {
+    let position = validators
+        .iter()
+        .position(|validator| validator == address)? as u64;
+    let validators_count = validators.len() as u64;
+    let step_duration = step_duration.max(1);
+
+    let current_step = now.as_secs() / step_duration;
+    let steps_ahead =
+        (position + validators_count - current_step % validators_count) % validators_count;
+    let next_step_start = Duration::from_secs((current_step + steps_ahead) * step_duration);
+
+    // Zero if our step is in progress, i.e. the block is being authored right now
+    Some(next_step_start.saturating_sub(now))
+}
+
+fn default_author_margin_secs() -> u64 {
+    DEFAULT_AUTHOR_MARGIN_SECS
+}
+
+fn default_grace_period_secs() -> u64 {
+    DEFAULT_GRACE_PERIOD_SECS
+}
+
+fn de_time<'de, D>(deserializer: D) -> Result<NaiveTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&value, "%H:%M:%S"))
+        .map_err(|e| serde::de::Error::custom(format!("Invalid time `{value}`: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_maintenance_window() {
+        let window: MaintenanceWindow =
+            serde_json::from_value(json!({ "start": "02:00", "end": "04:30" })).unwrap();
+        assert!(window.contains(time("02:00")));
+        assert!(window.contains(time("04:29")));
+        assert!(!window.contains(time("04:30")));
+        assert!(!window.contains(time("01:59")));
+
+        let overnight: MaintenanceWindow =
+            serde_json::from_value(json!({ "start": "23:00", "end": "01:00" })).unwrap();
+        assert!(overnight.contains(time("23:30")));
+        assert!(overnight.contains(time("00:30")));
+        assert!(!overnight.contains(time("12:00")));
+
+        assert_eq!(
+            window.remaining(time("04:00")),
+            Duration::from_secs(30 * 60)
+        );
+        assert_eq!(
+            overnight.remaining(time("23:30")),
+            Duration::from_secs(90 * 60)
+        );
+        assert_eq!(
+            overnight.remaining(time("00:30")),
+            Duration::from_secs(30 * 60)
+        );
+
+        assert!(serde_json::from_value::<MaintenanceWindow>(
+            json!({ "start": "25:00", "end": "01:00" })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_update_history() {
+        let now: DateTime<Utc> = "2024-11-01T10:00:00Z".parse().unwrap();
+        let attempt = |hours_ago: i64, nop_version: &str, outcome: UpdateOutcome| UpdateAttempt {
+            started_at: now - chrono::Duration::hours(hours_ago),
+            finished_at: now - chrono::Duration::hours(hours_ago),
+            scheduled: true,
+            nop_version: nop_version.to_owned(),
+            outcome,
+        };
+        let failed = || UpdateOutcome::Failed {
+            error: "Node is not healthy after update".to_owned(),
+        };
+
+        let mut history = UpdateHistory::default();
+        assert!(history.last_applied().is_none());
+        assert!(!history.is_retry_postponed("1.1.0", now));
+
+        history.attempts = vec![
+            attempt(48, "1.0.0", UpdateOutcome::Applied),
+            attempt(2, "1.1.0", failed()),
+        ];
+        assert_eq!(history.last_applied().unwrap().nop_version, "1.0.0");
+        assert!(history.is_retry_postponed("1.1.0", now));
+        assert!(!history.is_retry_postponed("1.1.1", now));
+        assert!(!history.is_retry_postponed("1.1.0", now + chrono::Duration::hours(23)));
+
+        history
+            .attempts
+            .push(attempt(1, "1.1.0", UpdateOutcome::Applied));
+        assert_eq!(history.last_applied().unwrap().nop_version, "1.1.0");
+        assert!(!history.is_retry_postponed("1.1.0", now));
+    }
+
+    #[test]
+    fn test_stagger_delay() {
+        let address = Address::repeat_byte(0x11);
+        let max = Duration::from_secs(900);
+
+        assert!(stagger_delay(&address, max) < max);
+        assert_eq!(stagger_delay(&address, max), stagger_delay(&address, max));
+        assert_ne!(
+            stagger_delay(&address, max),
+            stagger_delay(&Address::repeat_byte(0x22), max)
+        );
+        assert_eq!(stagger_delay(&address, Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_next_author_step() {
+        let validators = [
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            Address::repeat_byte(3),
+        ];
+
+        // Step 100 (500s) belongs to validator 100 % 3 = 1
+        let now = Duration::from_secs(500);
+        assert_eq!(
+            next_author_step_in(&validators, &validators[1], 5, now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            next_author_step_in(&validators, &validators[2], 5, now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            next_author_step_in(&validators, &validators[0], 5, now + Duration::from_secs(2)),
+            Some(Duration::from_secs(8))
+        );
+        assert_eq!(
+            next_author_step_in(&validators, &Address::repeat_byte(4), 5, now),
+            None
+        );
+    }
+}
@@ -1,16 +1,38 @@
 pub mod config;
 pub mod debug_info;
+pub mod diagnosis;
 pub mod exec;
+pub mod fleet;
+pub mod i18n;
 pub mod logger;
+pub mod logs;
+pub mod maintenance;
+pub mod net;
+pub mod ntp;
+pub mod profile;
+pub mod redact;
+pub mod release;
+#[cfg(test)]
+pub mod rpc_mock;
+pub mod sysinfo;
 
 use alloy::primitives::Address;
 use backtrace::Backtrace;
 use log::error;
 use serde::{de, Deserialize};
 use sha3::{Digest, Keccak256};
-use std::{panic, path::PathBuf, process::Output, thread};
-
-const DEFAULT_OUTPUT_DIRECTORY: &str = "./output";
+use std::{
+    ffi::OsString,
+    fs::Permissions,
+    os::unix::fs::PermissionsExt,
+    panic,
+    path::{Path, PathBuf},
+    process::Output,
+    thread,
+};
+use tokio::io::AsyncWriteExt;
+
+const PRIVATE_FILE_MODE: u32 = 0o600;
 
 pub fn set_heavy_panic() {
     panic::set_hook(Box::new(|panic_info| {
@@ -117,16 +139,49 @@ pub fn skip_hex_prefix(input: &str) -> &str {
     }
 }
 
+/// Tag of image reference, registry host may contain port, e.g. `registry.local:5000/openethereum:v1`
+pub fn image_tag(image: &str) -> Option<&str> {
+    image
+        .rsplit_once(':')
+        .map(|(_, tag)| tag)
+        .filter(|tag| !tag.contains('/'))
+}
+
 pub fn secp256k1_signing_key_to_eth_address(key: &k256::ecdsa::SigningKey) -> Address {
     get_eth_address(key.verifying_key().to_encoded_point(false).as_bytes())
 }
 
+/// Output directory of the active profile
 pub fn output_dir() -> PathBuf {
-    PathBuf::from(
-        std::env::var("OUTPUT_DIRECTORY")
-            .as_deref()
-            .unwrap_or(DEFAULT_OUTPUT_DIRECTORY),
-    )
+    profile::active().output_dir()
+}
+
+/// Writes `contents` into a temporary file next to `path` and renames it over `path`,
+/// so readers never see a partially written file. The file is readable by the owner only
+pub async fn write_file_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "No file name"))?;
+    let mut tmp_file_name = OsString::from(".");
+    tmp_file_name.push(file_name);
+    tmp_file_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_file_name);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(PRIVATE_FILE_MODE)
+        .open(&tmp_path)
+        .await?;
+    // Mode is applied on creation only, so fix leftovers of previous failed writes as well
+    file.set_permissions(Permissions::from_mode(PRIVATE_FILE_MODE))
+        .await?;
+    file.write_all(contents.as_ref()).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    tokio::fs::rename(&tmp_path, path).await
 }
 
 pub fn output_into_string(output: Result<Output, std::io::Error>) -> String {
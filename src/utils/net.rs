@@ -0,0 +1,172 @@
+use futures::future::join_all;
+use std::{
+    cmp::Reverse,
+    net::{IpAddr, Ipv4Addr},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::error::AppError;
+
+const PUBLIC_IP_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Public IPv4 discovery services. Parity can only advertise IPv4 address, so IPv6 isn't queried
+const PUBLIC_IP_SOURCES: [(&str, &str); 3] = [
+    ("ipify", "https://api.ipify.org"),
+    ("icanhazip", "https://ipv4.icanhazip.com"),
+    ("ident.me", "https://v4.ident.me"),
+];
+/// Interfaces created by docker which are never reachable from outside
+const IGNORED_INTERFACE_PREFIXES: [&str; 4] = ["docker", "br-", "veth", "lo"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceAddress {
+    pub interface: String,
+    pub ip: Ipv4Addr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredIp {
+    pub ip: IpAddr,
+    pub sources: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PublicIpDiscovery {
+    /// Discovered addresses, the most confirmed ones first
+    pub candidates: Vec<DiscoveredIp>,
+    /// Number of queried sources
+    pub sources_count: usize,
+}
+
+impl PublicIpDiscovery {
+    pub fn new(results: Vec<(&'static str, IpAddr)>, sources_count: usize) -> Self {
+        let mut candidates: Vec<DiscoveredIp> = Vec::new();
+        for (source, ip) in results {
+            match candidates.iter_mut().find(|candidate| candidate.ip == ip) {
+                Some(candidate) => candidate.sources.push(source),
+                None => candidates.push(DiscoveredIp {
+                    ip,
+                    sources: vec![source],
+                }),
+            }
+        }
+
+        candidates.sort_by_key(|candidate| Reverse(candidate.sources.len()));
+
+        Self {
+            candidates,
+            sources_count,
+        }
+    }
+
+    /// Returns `true` if all sources reported the same address
+    pub fn is_agreed(&self) -> bool {
+        self.candidates.len() <= 1
+    }
+
+    /// Most confirmed public address
+    pub fn best(&self) -> Option<IpAddr> {
+        self.candidates.first().map(|candidate| candidate.ip)
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.candidates.iter().any(|candidate| &candidate.ip == ip)
+    }
+}
+
+/// Queries every discovery service concurrently
+pub async fn discover_public_ips() -> Result<PublicIpDiscovery, AppError> {
+    let client = reqwest::Client::builder()
+        .timeout(PUBLIC_IP_REQUEST_TIMEOUT)
+        .build()?;
+
+    let requests = PUBLIC_IP_SOURCES.iter().map(|(name, url)| {
+        let client = client.clone();
+        async move {
+            let text = client.get(*url).send().await.ok()?.text().await.ok()?;
+            Ipv4Addr::from_str(text.trim())
+                .ok()
+                .map(|ip| (*name, IpAddr::V4(ip)))
+        }
+    });
+
+    let results = join_all(requests).await.into_iter().flatten().collect();
+
+    Ok(PublicIpDiscovery::new(results, PUBLIC_IP_SOURCES.len()))
+}
+
+/// IPv4 addresses assigned to host network interfaces, except loopback, link-local and unspecified
+/// ones. Private addresses are kept, since the node may be reachable through port forwarding
+pub fn local_interface_addresses() -> Result<Vec<InterfaceAddress>, AppError> {
+    let mut addresses = nix::ifaddrs::getifaddrs()
+        .map_err(std::io::Error::from)?
+        .filter(|ifaddr| {
+            !IGNORED_INTERFACE_PREFIXES
+                .iter()
+                .any(|prefix| ifaddr.interface_name.starts_with(prefix))
+        })
+        .filter_map(|ifaddr| {
+            let ip = ifaddr.address?.as_sockaddr_in()?.ip();
+
+            is_usable_address(&ip).then_some(InterfaceAddress {
+                interface: ifaddr.interface_name,
+                ip,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    addresses.dedup();
+    Ok(addresses)
+}
+
+fn is_usable_address(ip: &Ipv4Addr) -> bool {
+    !ip.is_loopback() && !ip.is_link_local() && !ip.is_unspecified()
+}
+
+/// Returns `true` for addresses routable on the internet, i.e. not private or shared ones
+pub fn is_global_address(ip: &Ipv4Addr) -> bool {
+    // `100.64.0.0/10` is shared by carrier-grade NAT
+    is_usable_address(ip)
+        && !ip.is_private()
+        && !(ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_ip_discovery() {
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let other_ip: IpAddr = "198.51.100.1".parse().unwrap();
+
+        let discovery =
+            PublicIpDiscovery::new(vec![("ipify", ip), ("icanhazip", ip), ("ident.me", ip)], 3);
+        assert!(discovery.is_agreed());
+        assert_eq!(discovery.best(), Some(ip));
+        assert!(discovery.contains(&ip));
+        assert!(!discovery.contains(&other_ip));
+
+        let discovery = PublicIpDiscovery::new(
+            vec![("ipify", other_ip), ("icanhazip", ip), ("ident.me", ip)],
+            3,
+        );
+        assert!(!discovery.is_agreed());
+        assert_eq!(discovery.best(), Some(ip));
+        assert_eq!(discovery.candidates[1].sources, vec!["ipify"]);
+
+        assert!(PublicIpDiscovery::new(Vec::new(), 3).best().is_none());
+    }
+
+    #[test]
+    fn test_interface_address_kinds() {
+        assert!(!is_usable_address(&"169.254.0.1".parse().unwrap()));
+        assert!(!is_usable_address(&"127.0.0.1".parse().unwrap()));
+        assert!(!is_usable_address(&"0.0.0.0".parse().unwrap()));
+
+        assert!(is_global_address(&"203.0.113.7".parse().unwrap()));
+        assert!(is_usable_address(&"192.168.1.10".parse().unwrap()));
+        assert!(!is_global_address(&"192.168.1.10".parse().unwrap()));
+        assert!(!is_global_address(&"100.64.0.1".parse().unwrap()));
+    }
+}
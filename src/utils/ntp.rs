@@ -0,0 +1,76 @@
+use anyhow::anyhow;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+
+use crate::error::AppError;
+
+pub const DEFAULT_NTP_SERVER: &str = "pool.ntp.org:123";
+
+/// Seconds between NTP era start (1900) and Unix epoch (1970)
+const NTP_UNIX_EPOCH_DELTA: f64 = 2_208_988_800.0;
+const NTP_PACKET_SIZE: usize = 48;
+/// LI = 0 (no warning), VN = 3, Mode = 3 (client)
+const NTP_CLIENT_REQUEST_HEADER: u8 = 0x1B;
+
+/// Queries SNTP server and returns local clock offset in seconds (positive if local clock is behind)
+pub async fn clock_offset(server: &str, timeout: Duration) -> Result<f64, AppError> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+
+    let mut request = [0u8; NTP_PACKET_SIZE];
+    request[0] = NTP_CLIENT_REQUEST_HEADER;
+
+    let originate = unix_time_now();
+    socket.send(&request).await?;
+
+    let mut response = [0u8; NTP_PACKET_SIZE];
+    let received = tokio::time::timeout(timeout, socket.recv(&mut response))
+        .await
+        .map_err(|_| anyhow!("NTP server `{server}` did not respond in {timeout:?}"))??;
+    let destination = unix_time_now();
+
+    if received < NTP_PACKET_SIZE {
+        return Err(anyhow!("Malformed NTP response of {received} bytes").into());
+    }
+
+    let receive = parse_ntp_timestamp(&response[32..40]);
+    let transmit = parse_ntp_timestamp(&response[40..48]);
+
+    Ok(ntp_offset(originate, receive, transmit, destination))
+}
+
+fn unix_time_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Converts 64-bit NTP timestamp (32 bits of seconds and 32 bits of fraction) into Unix time
+fn parse_ntp_timestamp(bytes: &[u8]) -> f64 {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as f64;
+
+    seconds - NTP_UNIX_EPOCH_DELTA + fraction / u32::MAX as f64
+}
+
+fn ntp_offset(originate: f64, receive: f64, transmit: f64, destination: f64) -> f64 {
+    ((receive - originate) + (transmit - destination)) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ntp_offset() {
+        // 2024-01-01T00:00:00Z with half a second fraction
+        let seconds = (1_704_067_200u64 + NTP_UNIX_EPOCH_DELTA as u64) as u32;
+        let bytes = [seconds.to_be_bytes(), (u32::MAX / 2).to_be_bytes()].concat();
+        assert!((parse_ntp_timestamp(&bytes) - 1_704_067_200.5).abs() < 1e-6);
+
+        // Server clock is 2 seconds ahead, network delay is 0.1 second each way
+        assert!((ntp_offset(100.0, 102.1, 102.2, 100.3) - 2.0).abs() < 1e-9);
+        assert!((ntp_offset(100.0, 100.1, 100.2, 100.3)).abs() < 1e-9);
+    }
+}
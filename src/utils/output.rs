@@ -0,0 +1,325 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    future::Future,
+    io::{self, Write},
+    sync::OnceLock,
+    time::Instant,
+};
+
+use crate::{
+    error::AppError,
+    messages::{MessageKey, MessageType},
+};
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Interactive terminal UI
+    #[default]
+    Text,
+    /// One JSON event per line on stdout, prompts are disabled
+    Jsonl,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Level {
+    Info,
+    Step,
+    Success,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "event")]
+pub enum Event<'a> {
+    PhaseStarted {
+        phase: &'a str,
+    },
+    PhaseFinished {
+        phase: &'a str,
+        duration_ms: u64,
+    },
+    PhaseFailed {
+        phase: &'a str,
+        code: &'static str,
+        message: String,
+    },
+    PhaseSkipped {
+        phase: &'a str,
+        reason: &'static str,
+    },
+    PhasePlanned {
+        phase: &'a str,
+        action: &'static str,
+        interactive: bool,
+        dependencies: &'a [&'a str],
+    },
+    Log {
+        level: Level,
+        code: &'static str,
+        message: String,
+    },
+    Note {
+        title: String,
+        message: String,
+    },
+    /// Machine-readable result of a command, e.g. `fleet --format json` or a line of `logs`
+    Report {
+        name: &'a str,
+        data: serde_json::Value,
+    },
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+/// Text with a stable code, so consumers of the event stream don't depend on wording
+pub trait Coded: Display {
+    fn code(&self) -> &'static str;
+}
+
+impl Coded for MessageType<'_> {
+    fn code(&self) -> &'static str {
+        MessageKey::from(self).into()
+    }
+}
+
+impl Coded for AppError {
+    fn code(&self) -> &'static str {
+        AppError::code(self)
+    }
+}
+
+impl<T: Coded + ?Sized> Coded for &T {
+    fn code(&self) -> &'static str {
+        (**self).code()
+    }
+}
+
+pub fn init(format: OutputFormat) {
+    let _ = OUTPUT_FORMAT.set(format);
+}
+
+pub fn is_jsonl() -> bool {
+    OUTPUT_FORMAT.get() == Some(&OutputFormat::Jsonl)
+}
+
+/// Writes the event to stdout in JSON lines mode, does nothing otherwise
+pub fn emit(event: Event<'_>) -> io::Result<()> {
+    if !is_jsonl() {
+        return Ok(());
+    }
+
+    let line = serde_json::to_string(&Envelope {
+        timestamp: Utc::now(),
+        event,
+    })?;
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{line}")?;
+    stdout.flush()
+}
+
+/// Fails if the user has to be asked, since prompts are unavailable in JSON lines mode
+pub fn require_interactive(input: &str) -> Result<(), AppError> {
+    if is_jsonl() {
+        Err(AppError::InputRequired(input.to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn note(title: impl Display, message: impl Display) -> io::Result<()> {
+    if is_jsonl() {
+        emit(Event::Note {
+            title: title.to_string(),
+            message: message.to_string(),
+        })
+    } else {
+        cliclack::note(title, message)
+    }
+}
+
+/// Reports a step which doesn't run, because its result is already available
+pub fn skip(phase: &str, reason: &'static str, message: impl Coded) -> io::Result<()> {
+    if is_jsonl() {
+        emit(Event::PhaseSkipped { phase, reason })
+    } else {
+        cliclack::log::info(message)
+    }
+}
+
+/// Runs a step of the flow, reporting its start and outcome as events
+pub async fn track<T>(
+    phase: &str,
+    step: impl Future<Output = Result<T, AppError>>,
+) -> Result<T, AppError> {
+    emit(Event::PhaseStarted { phase })?;
+    let started_at = Instant::now();
+
+    let result = step.await;
+    match &result {
+        Ok(_) => emit(Event::PhaseFinished {
+            phase,
+            duration_ms: started_at.elapsed().as_millis() as u64,
+        })?,
+        Err(e) => emit(Event::PhaseFailed {
+            phase,
+            code: e.code(),
+            message: e.to_string(),
+        })?,
+    }
+
+    result
+}
+
+/// Progress spinner, reported as log events in JSON lines mode
+pub struct Spinner(Option<cliclack::ProgressBar>);
+
+pub fn spinner() -> Spinner {
+    Spinner((!is_jsonl()).then(cliclack::spinner))
+}
+
+impl Spinner {
+    pub fn start(&self, message: impl Coded) {
+        match &self.0 {
+            Some(spinner) => spinner.start(message),
+            None => {
+                let _ = log::step(message);
+            }
+        }
+    }
+
+    pub fn set_message(&self, message: impl Coded) {
+        match &self.0 {
+            Some(spinner) => spinner.set_message(message),
+            None => {
+                let _ = log::step(message);
+            }
+        }
+    }
+
+    pub fn stop(&self, message: impl Coded) {
+        match &self.0 {
+            Some(spinner) => spinner.stop(message),
+            None => {
+                let _ = log::success(message);
+            }
+        }
+    }
+
+    pub fn error(&self, message: impl Coded) {
+        match &self.0 {
+            Some(spinner) => spinner.error(message),
+            None => {
+                let _ = log::error(message);
+            }
+        }
+    }
+}
+
+pub mod log {
+    use super::*;
+
+    fn log(level: Level, message: impl Coded) -> io::Result<()> {
+        if is_jsonl() {
+            return emit(Event::Log {
+                level,
+                code: message.code(),
+                message: message.to_string(),
+            });
+        }
+
+        match level {
+            Level::Info => cliclack::log::info(message),
+            Level::Step => cliclack::log::step(message),
+            Level::Success => cliclack::log::success(message),
+            Level::Warning => cliclack::log::warning(message),
+            Level::Error => cliclack::log::error(message),
+        }
+    }
+
+    pub fn info(message: impl Coded) -> io::Result<()> {
+        log(Level::Info, message)
+    }
+
+    pub fn step(message: impl Coded) -> io::Result<()> {
+        log(Level::Step, message)
+    }
+
+    pub fn success(message: impl Coded) -> io::Result<()> {
+        log(Level::Success, message)
+    }
+
+    pub fn warning(message: impl Coded) -> io::Result<()> {
+        log(Level::Warning, message)
+    }
+
+    pub fn error(message: impl Coded) -> io::Result<()> {
+        log(Level::Error, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = serde_json::to_value(Envelope {
+            timestamp: "2024-11-01T10:00:00Z".parse().unwrap(),
+            event: Event::PhaseFailed {
+                phase: "setup",
+                code: AppError::InputRequired("network".to_owned()).code(),
+                message: "Input required".to_owned(),
+            },
+        })
+        .unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "timestamp": "2024-11-01T10:00:00Z",
+                "event": "phaseFailed",
+                "phase": "setup",
+                "code": "input_required",
+                "message": "Input required",
+            })
+        );
+
+        let log = serde_json::to_value(Event::Log {
+            level: Level::Warning,
+            code: MessageType::P2pNoPeers { port: 30303 }.code(),
+            message: String::new(),
+        })
+        .unwrap();
+        assert_eq!(log["level"], json!("warning"));
+        assert_eq!(log["code"], json!("P2pNoPeers"));
+
+        let finished = serde_json::to_value(Event::PhaseFinished {
+            phase: "setup",
+            duration_ms: 1500,
+        })
+        .unwrap();
+        assert_eq!(finished["duration_ms"], json!(1500));
+
+        let report = serde_json::to_string(&Event::Report {
+            name: "fleet",
+            data: json!([{ "name": "node" }]),
+        })
+        .unwrap();
+        assert!(!report.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&report).unwrap()["event"],
+            json!("report")
+        );
+    }
+}
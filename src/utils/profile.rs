@@ -0,0 +1,282 @@
+use anyhow::anyhow;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::{LazyLock, OnceLock},
+};
+
+use crate::{error::AppError, state::State, utils::config::ConfigPath};
+
+pub const PROFILE_ENV: &str = "NOP_PROFILE";
+pub const DEFAULT_PROFILE_NAME: &str = "default";
+
+const DEFAULT_OUTPUT_DIRECTORY: &str = "./output";
+const DEFAULT_STATE_PATH: ConfigPath<'_> = ConfigPath::Relative {
+    root: "./",
+    path: "./state.json",
+};
+const PROFILES_DIRECTORY: &str = "./profiles";
+const PROFILE_STATE_FILE_NAME: &str = "state.json";
+const PROFILE_OUTPUT_DIRECTORY: &str = "output";
+/// Prefix of docker compose project names of named profiles
+const PROJECT_NAME_PREFIX: &str = "nop-";
+
+const LOCAL_RPC_HOST: &str = "127.0.0.1";
+const DEFAULT_P2P_PORT: u16 = 30303;
+const DEFAULT_RPC_PORT: u16 = 8545;
+/// Upper bound of nodes on a single host, protects port allocation from looping forever
+const MAX_PORT_OFFSET: u16 = 1000;
+
+/// Docker compose project names allow lowercase letters, digits, dashes and underscores only
+static PROFILE_NAME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z0-9][a-z0-9_-]{0,31}$").unwrap());
+
+static ACTIVE_PROFILE: OnceLock<Profile> = OnceLock::new();
+
+/// Node managed by this NOP install. The default profile keeps the layout used before profiles
+/// were introduced, named ones live in `./profiles/<name>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    name: Option<String>,
+}
+
+/// Host ports published by the node
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodePorts {
+    pub p2p: u16,
+    pub rpc: u16,
+}
+
+impl Default for NodePorts {
+    fn default() -> Self {
+        Self {
+            p2p: DEFAULT_P2P_PORT,
+            rpc: DEFAULT_RPC_PORT,
+        }
+    }
+}
+
+impl NodePorts {
+    /// Picks the first pair of ports after the default ones which isn't used by other nodes
+    pub fn allocate(used: &[NodePorts]) -> Result<Self, AppError> {
+        (0..MAX_PORT_OFFSET)
+            .map(|offset| Self {
+                p2p: DEFAULT_P2P_PORT + offset,
+                rpc: DEFAULT_RPC_PORT + offset,
+            })
+            .find(|ports| {
+                !used
+                    .iter()
+                    .any(|other| other.p2p == ports.p2p || other.rpc == ports.rpc)
+            })
+            .ok_or_else(|| anyhow!("No free ports left for a new node").into())
+    }
+}
+
+impl Profile {
+    /// `None` and `default` both select the default profile
+    pub fn new(name: Option<&str>) -> Result<Self, AppError> {
+        match name {
+            None | Some(DEFAULT_PROFILE_NAME) => Ok(Self { name: None }),
+            Some(name) if PROFILE_NAME_REGEX.is_match(name) => Ok(Self {
+                name: Some(name.to_owned()),
+            }),
+            Some(name) => Err(anyhow!(
+                "Invalid profile name `{name}`, use lowercase letters, digits, `-` and `_`"
+            )
+            .into()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(DEFAULT_PROFILE_NAME)
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.name.is_none()
+    }
+
+    fn root(name: &str) -> PathBuf {
+        PathBuf::from(PROFILES_DIRECTORY).join(name)
+    }
+
+    pub fn state_path(&self) -> PathBuf {
+        match &self.name {
+            Some(name) => Self::root(name).join(PROFILE_STATE_FILE_NAME),
+            None => match std::env::var("STORE_PATH").as_deref() {
+                Ok(path) => PathBuf::from(&ConfigPath::Absolute { path }),
+                Err(_) => PathBuf::from(&DEFAULT_STATE_PATH),
+            },
+        }
+    }
+
+    pub fn output_dir(&self) -> PathBuf {
+        match &self.name {
+            Some(name) => Self::root(name).join(PROFILE_OUTPUT_DIRECTORY),
+            None => PathBuf::from(
+                std::env::var("OUTPUT_DIRECTORY")
+                    .as_deref()
+                    .unwrap_or(DEFAULT_OUTPUT_DIRECTORY),
+            ),
+        }
+    }
+
+    /// Output directory as seen by the docker host, `host_output_dir` is `HOST_OUTPUT_DIRECTORY`.
+    /// Named profiles get their own subdirectory, so their nodes don't mount the same files
+    pub fn host_output_dir(&self, host_output_dir: &str) -> String {
+        match &self.name {
+            Some(name) => Path::new(host_output_dir)
+                .join(PROFILES_DIRECTORY.trim_start_matches("./"))
+                .join(name)
+                .join(PROFILE_OUTPUT_DIRECTORY)
+                .to_string_lossy()
+                .into_owned(),
+            None => host_output_dir.to_owned(),
+        }
+    }
+
+    /// Docker compose project, `None` keeps the project name derived from output directory,
+    /// so containers started before profiles were introduced stay in the same project
+    pub fn project_name(&self) -> Option<String> {
+        self.name
+            .as_ref()
+            .map(|name| format!("{PROJECT_NAME_PREFIX}{name}"))
+    }
+
+    /// Prefix of container names, so nodes of different profiles don't clash
+    pub fn container_prefix(&self) -> String {
+        self.name
+            .as_ref()
+            .map(|name| format!("{name}_"))
+            .unwrap_or_default()
+    }
+
+    pub fn container_name(&self, name: &str) -> String {
+        format!("{}{name}", self.container_prefix())
+    }
+
+    /// RPC of the local node, `PARITY_URL` overrides it for the default profile only
+    pub fn local_rpc_url(&self, ports: &NodePorts) -> Result<reqwest::Url, AppError> {
+        match std::env::var("PARITY_URL") {
+            Ok(url) if self.is_default() => url.parse(),
+            _ => format!("http://{LOCAL_RPC_HOST}:{}", ports.rpc).parse(),
+        }
+        .map_err(AppError::from)
+    }
+
+    /// Profile state, missing state file means an empty one
+    pub fn state(&self) -> Result<State, AppError> {
+        State::read_from(self.state_path())
+    }
+}
+
+/// Selects profile used by the rest of the process, must be called before anything reads
+/// state or output directory
+pub fn init(profile: Profile) -> Result<(), AppError> {
+    ACTIVE_PROFILE
+        .set(profile)
+        .map_err(|profile| anyhow!("Profile is already selected: {}", profile.name()).into())
+}
+
+pub fn active() -> &'static Profile {
+    ACTIVE_PROFILE.get_or_init(|| Profile { name: None })
+}
+
+/// Configured profiles on this host: the default one if it has state, followed by named ones
+pub fn list() -> Result<Vec<Profile>, AppError> {
+    let default = Profile { name: None };
+    let mut profiles = Vec::new();
+    if default.state_path().exists() {
+        profiles.push(default);
+    }
+
+    let mut names = match std::fs::read_dir(PROFILES_DIRECTORY) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().join(PROFILE_STATE_FILE_NAME).exists())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| PROFILE_NAME_REGEX.is_match(name))
+            .collect::<Vec<_>>(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    names.sort();
+    profiles.extend(names.into_iter().map(|name| Profile { name: Some(name) }));
+
+    Ok(profiles)
+}
+
+/// Ports taken by all profiles except the given one. Default ports are always reserved for
+/// the default profile, even if it isn't configured yet
+pub fn used_ports(except: &Profile) -> Result<Vec<NodePorts>, AppError> {
+    let mut ports = list()?
+        .into_iter()
+        .filter(|profile| profile != except)
+        .map(|profile| profile.state().map(|state| state.ports()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !except.is_default() {
+        ports.push(NodePorts::default());
+    }
+
+    Ok(ports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile() {
+        let default = Profile::new(None).unwrap();
+        assert_eq!(default, Profile::new(Some(DEFAULT_PROFILE_NAME)).unwrap());
+        assert!(default.is_default());
+        assert_eq!(default.project_name(), None);
+        assert_eq!(default.container_name("parity"), "parity");
+        assert_eq!(
+            default.host_output_dir("/srv/nop/output"),
+            "/srv/nop/output"
+        );
+
+        let named = Profile::new(Some("testnet-2")).unwrap();
+        assert_eq!(named.name(), "testnet-2");
+        assert_eq!(named.project_name().as_deref(), Some("nop-testnet-2"));
+        assert_eq!(named.container_name("parity"), "testnet-2_parity");
+        assert_eq!(
+            named.output_dir(),
+            PathBuf::from("./profiles/testnet-2/output")
+        );
+        assert_eq!(
+            named.state_path(),
+            PathBuf::from("./profiles/testnet-2/state.json")
+        );
+        assert_eq!(
+            named.host_output_dir("/srv/nop/output"),
+            "/srv/nop/output/profiles/testnet-2/output"
+        );
+
+        assert!(Profile::new(Some("Main")).is_err());
+        assert!(Profile::new(Some("../main")).is_err());
+        assert!(Profile::new(Some("")).is_err());
+    }
+
+    #[test]
+    fn test_allocate_ports() {
+        assert_eq!(NodePorts::allocate(&[]).unwrap(), NodePorts::default());
+
+        let used = [
+            NodePorts::default(),
+            NodePorts {
+                p2p: 30304,
+                rpc: 8600,
+            },
+        ];
+        assert_eq!(
+            NodePorts::allocate(&used).unwrap(),
+            NodePorts {
+                p2p: 30305,
+                rpc: 8547
+            }
+        );
+    }
+}
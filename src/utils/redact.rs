@@ -0,0 +1,88 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+const REDACTED: &str = "<redacted>";
+const REDACTED_IP: &str = "<redacted ip>";
+
+static KEYSTORE_FIELD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"("(?:ciphertext|salt|iv|mac)"\s*:\s*)"[0-9a-fA-F]*""#).unwrap());
+static IPV4_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap());
+static IPV6_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:[0-9a-fA-F]{1,4}:){7}[0-9a-fA-F]{1,4}\b|\b(?:[0-9a-fA-F]{1,4}:){1,7}:(?:[0-9a-fA-F]{1,4}(?::[0-9a-fA-F]{1,4}){0,6})?\b").unwrap()
+});
+
+/// Removes secrets (and optionally IP addresses) from debug information before it leaves the host
+#[derive(Debug, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+    redact_ips: bool,
+}
+
+impl Redactor {
+    pub fn new(redact_ips: bool) -> Self {
+        Self {
+            secrets: Vec::new(),
+            redact_ips,
+        }
+    }
+
+    /// Adds a secret which will be replaced in any form it's found (with or without `0x` prefix)
+    pub fn with_secret(mut self, secret: impl AsRef<str>) -> Self {
+        let secret = secret.as_ref().trim();
+        let secret = super::skip_hex_prefix(secret);
+        if !secret.is_empty() {
+            self.secrets.push(secret.to_owned());
+        }
+        self
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = self.secrets.iter().fold(text.to_owned(), |text, secret| {
+            text.replace(&format!("0x{secret}"), REDACTED)
+                .replace(secret, REDACTED)
+        });
+
+        text = KEYSTORE_FIELD_REGEX
+            .replace_all(&text, format!("${{1}}\"{REDACTED}\""))
+            .into_owned();
+
+        if self.redact_ips {
+            text = IPV4_REGEX.replace_all(&text, REDACTED_IP).into_owned();
+            text = IPV6_REGEX.replace_all(&text, REDACTED_IP).into_owned();
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let key = "abababababababababababababababababababababababababababababababab";
+        let password = "0xcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd";
+        let redactor = Redactor::new(false)
+            .with_secret(format!("0x{key}"))
+            .with_secret(password);
+
+        assert_eq!(
+            redactor.redact(&format!("privateKey: 0x{key}, raw: {key}")),
+            "privateKey: <redacted>, raw: <redacted>"
+        );
+        assert_eq!(redactor.redact(password), "<redacted>");
+        assert_eq!(
+            redactor.redact(r#"{"crypto":{"ciphertext":"ab01","mac": "cd02"},"id":"1"}"#),
+            r#"{"crypto":{"ciphertext":"<redacted>","mac": "<redacted>"},"id":"1"}"#
+        );
+        assert_eq!(redactor.redact("extip:10.0.0.1"), "extip:10.0.0.1");
+
+        let redactor = Redactor::new(true);
+        assert_eq!(
+            redactor.redact("nat = \"extip:10.0.0.1\" v6 2001:db8::1 block 0x12ab"),
+            "nat = \"extip:<redacted ip>\" v6 <redacted ip> block 0x12ab"
+        );
+    }
+}
@@ -0,0 +1,734 @@
+use anyhow::anyhow;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use p256::{
+    ecdsa::{signature::Verifier, Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+};
+use semver::Version;
+use serde::Deserialize;
+use std::{
+    ffi::OsString,
+    fs::Permissions,
+    io::{Cursor, Read},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+use tokio::io::AsyncWriteExt;
+
+use super::sysinfo::OsRelease;
+use crate::error::AppError;
+
+pub const BINARY_FILE_NAME: &str = "airdao-nop-rs";
+pub const SIGNATURE_FILE_NAME: &str = "airdao-nop-rs.sig";
+/// Cosign public key installed next to the binary
+pub const PUBLIC_KEY_FILE_NAME: &str = "airdao-nop-rs.pub";
+
+/// Runtime files shipped with the binary, installed next to it
+const BUNDLE_DIRS: [&str; 2] = ["config", "setup_templates"];
+const UPDATE_SCRIPT_FILE_NAME: &str = "update.sh";
+/// Directory next to the binary with the files replaced by the last self-update
+const BACKUP_DIR_NAME: &str = ".self-update-backup";
+
+const ARTIFACT_NAME: &str = "airdao-nop-rs-x86-64.zip";
+/// Built against older glibc
+const ARTIFACT_NAME_OLD: &str = "airdao-nop-rs-x86-64-old.zip";
+const EXECUTABLE_FILE_MODE: u32 = 0o755;
+const DATA_FILE_MODE: u32 = 0o644;
+
+/// Release body line with the oldest NOP version which still works with the network,
+/// e.g. `min-supported-version: 0.1.6`
+const MIN_SUPPORTED_VERSION_KEY: &str = "min-supported-version";
+/// Release body line marking an update which must be installed as soon as possible,
+/// e.g. `critical: true`
+const CRITICAL_KEY: &str = "critical";
+
+/// Release in GitHub releases API format
+#[derive(Deserialize, Debug)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: reqwest::Url,
+}
+
+/// Update metadata published in release body
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseMetadata {
+    pub min_supported_version: Option<Version>,
+    pub critical: bool,
+}
+
+/// Running NOP version compared with the latest release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionStatus {
+    UpToDate,
+    Outdated {
+        latest: Version,
+        critical: bool,
+    },
+    /// Running version is older than the minimum supported one
+    Unsupported {
+        latest: Version,
+        min_supported: Version,
+    },
+}
+
+/// Binary, its signature and runtime files extracted from release artifact
+pub struct ReleaseBundle {
+    pub binary: Vec<u8>,
+    pub signature: String,
+    /// Config, setup templates and update script, relative to the binary directory
+    pub files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl Release {
+    /// Version without `v` prefix
+    pub fn version(&self) -> Result<Version, AppError> {
+        let version = self.tag_name.trim_start_matches('v');
+        Version::parse(version)
+            .map_err(|e| anyhow!("Invalid release version `{version}`: {e}").into())
+    }
+
+    pub fn metadata(&self) -> ReleaseMetadata {
+        let mut metadata = ReleaseMetadata::default();
+
+        for line in self.body.as_deref().unwrap_or_default().lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('`');
+
+            match key
+                .trim()
+                .trim_start_matches(['-', '*', ' '])
+                .to_lowercase()
+                .as_str()
+            {
+                MIN_SUPPORTED_VERSION_KEY => {
+                    metadata.min_supported_version =
+                        Version::parse(value.trim_start_matches('v')).ok()
+                }
+                CRITICAL_KEY => metadata.critical = value.eq_ignore_ascii_case("true"),
+                _ => {}
+            }
+        }
+
+        metadata
+    }
+
+    /// Compares `current` version with this release
+    pub fn version_status(&self, current: &Version) -> Result<VersionStatus, AppError> {
+        let latest = self.version()?;
+        let metadata = self.metadata();
+
+        Ok(match metadata.min_supported_version {
+            Some(min_supported) if *current < min_supported => VersionStatus::Unsupported {
+                latest,
+                min_supported,
+            },
+            _ if *current < latest => VersionStatus::Outdated {
+                latest,
+                critical: metadata.critical,
+            },
+            _ => VersionStatus::UpToDate,
+        })
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Version of the running binary
+pub fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("Package version is semver")
+}
+
+pub async fn fetch_latest(feed_url: &reqwest::Url) -> Result<Release, AppError> {
+    reqwest::Client::new()
+        .get(feed_url.clone())
+        // GitHub API rejects requests without user agent
+        .header(reqwest::header::USER_AGENT, BINARY_FILE_NAME)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Release>()
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn download(url: &reqwest::Url) -> Result<Vec<u8>, AppError> {
+    Ok(reqwest::Client::new()
+        .get(url.clone())
+        .header(reqwest::header::USER_AGENT, BINARY_FILE_NAME)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+/// Release artifact built for the host
+pub fn artifact_name(os_release: &OsRelease, arch: &str) -> Result<&'static str, AppError> {
+    if arch != "x86_64" {
+        return Err(anyhow!("No release artifact for `{arch}` architecture").into());
+    }
+
+    let major_version = os_release
+        .version_id
+        .split('.')
+        .next()
+        .and_then(|major| major.parse::<u32>().ok());
+
+    match (os_release.id.as_str(), major_version) {
+        ("ubuntu", Some(major)) if major >= 22 => Ok(ARTIFACT_NAME),
+        ("debian", Some(major)) if major > 11 => Ok(ARTIFACT_NAME),
+        ("ubuntu" | "debian", Some(_)) => Ok(ARTIFACT_NAME_OLD),
+        _ => Err(anyhow!(
+            "No release artifact for `{} {}` distribution",
+            os_release.id,
+            os_release.version_id
+        )
+        .into()),
+    }
+}
+
+/// Extracts the binary, its signature and runtime files from release zip archive
+pub fn extract_bundle(archive: &[u8]) -> Result<ReleaseBundle, AppError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive))?;
+    let mut binary = None;
+    let mut signature = None;
+    let mut files = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if !file.is_file() {
+            continue;
+        }
+
+        let Some(path) = file.enclosed_name() else {
+            return Err(anyhow!("Unsafe path `{}` in release archive", file.name()).into());
+        };
+        // Files are packed into a directory
+        let path = path.components().skip(1).collect::<PathBuf>();
+        if path == Path::new(BINARY_FILE_NAME) {
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            binary = Some(content);
+        } else if path == Path::new(SIGNATURE_FILE_NAME) {
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            signature = Some(content);
+        } else if is_bundle_file(&path) {
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            files.push((path, content));
+        }
+    }
+
+    match (binary, signature) {
+        (Some(binary), Some(signature)) => Ok(ReleaseBundle {
+            binary,
+            signature,
+            files,
+        }),
+        (None, _) => Err(anyhow!("`{BINARY_FILE_NAME}` not found in release archive").into()),
+        (_, None) => Err(anyhow!("`{SIGNATURE_FILE_NAME}` not found in release archive").into()),
+    }
+}
+
+/// Public key is deliberately not a bundle file, the installed one stays the trusted key
+fn is_bundle_file(path: &Path) -> bool {
+    path == Path::new(UPDATE_SCRIPT_FILE_NAME)
+        || BUNDLE_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Verifies `cosign sign-blob` signature (base64 encoded DER ECDSA P-256 over SHA-256)
+/// with PEM encoded public key
+pub fn verify_signature(binary: &[u8], signature: &str, public_key: &str) -> Result<(), AppError> {
+    let public_key = VerifyingKey::from_public_key_pem(public_key)
+        .map_err(|e| anyhow!("Invalid release public key: {e}"))?;
+    let signature = BASE64_STANDARD
+        .decode(signature.trim())
+        .map_err(|e| anyhow!("Invalid release signature encoding: {e}"))?;
+
+    public_key
+        .verify(binary, &Signature::from_der(&signature)?)
+        .map_err(|_| anyhow!("Release signature verification failed").into())
+}
+
+/// Replaces the binary at `binary_path` and the bundle files next to it. Replaced files are kept
+/// as a backup and restored if the new binary doesn't report `expected_version`
+pub async fn install_bundle(
+    binary_path: &Path,
+    bundle: &ReleaseBundle,
+    expected_version: &str,
+) -> Result<(), AppError> {
+    let new_path = sibling_path(binary_path, "new");
+    write_file(&new_path, &bundle.binary, EXECUTABLE_FILE_MODE).await?;
+
+    // Checked before the swap, so a binary which doesn't run on this host never replaces the working one
+    if let Err(e) = check_version(&new_path, expected_version).await {
+        let _ = tokio::fs::remove_file(&new_path).await;
+        return Err(e);
+    }
+
+    let install_dir = binary_path.parent().unwrap_or(Path::new("."));
+    let binary_file_name = PathBuf::from(binary_path.file_name().unwrap_or_default());
+    let backup = Backup::take(
+        install_dir,
+        bundle
+            .files
+            .iter()
+            .map(|(path, _)| path.as_path())
+            .chain([binary_file_name.as_path()]),
+    )
+    .await?;
+
+    let result: Result<(), AppError> = async {
+        for (path, content) in &bundle.files {
+            let mode = if path == Path::new(UPDATE_SCRIPT_FILE_NAME) {
+                EXECUTABLE_FILE_MODE
+            } else {
+                DATA_FILE_MODE
+            };
+            // Renamed into place, so the running `update.sh` keeps reading its old content
+            let path = install_dir.join(path);
+            let tmp_path = sibling_path(&path, "new");
+            write_file(&tmp_path, content, mode).await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+        }
+        tokio::fs::rename(&new_path, binary_path).await?;
+
+        check_version(binary_path, expected_version).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&new_path).await;
+        backup.restore().await?;
+        return Err(anyhow!("New release is broken, previous one is restored: {e}").into());
+    }
+
+    Ok(())
+}
+
+/// Copies of the installed files replaced by self-update
+struct Backup {
+    install_dir: PathBuf,
+    dir: PathBuf,
+    /// Files which existed and are copied into backup directory
+    saved: Vec<PathBuf>,
+    /// Files which are new in the release, removed on restore
+    created: Vec<PathBuf>,
+}
+
+impl Backup {
+    /// Replaces the backup of the previous self-update
+    async fn take(
+        install_dir: &Path,
+        paths: impl Iterator<Item = &Path>,
+    ) -> Result<Self, AppError> {
+        let dir = install_dir.join(BACKUP_DIR_NAME);
+        match tokio::fs::remove_dir_all(&dir).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        let mut saved = Vec::new();
+        let mut created = Vec::new();
+        for path in paths {
+            let source = install_dir.join(path);
+            if !tokio::fs::try_exists(&source).await? {
+                created.push(path.to_owned());
+                continue;
+            }
+
+            let target = dir.join(path);
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            // Permissions are copied as well
+            tokio::fs::copy(&source, &target).await?;
+            saved.push(path.to_owned());
+        }
+
+        Ok(Self {
+            install_dir: install_dir.to_owned(),
+            dir,
+            saved,
+            created,
+        })
+    }
+
+    async fn restore(&self) -> Result<(), AppError> {
+        for path in &self.saved {
+            let target = self.install_dir.join(path);
+            let tmp_path = sibling_path(&target, "new");
+            tokio::fs::copy(self.dir.join(path), &tmp_path).await?;
+            tokio::fs::rename(&tmp_path, &target).await?;
+        }
+        for path in &self.created {
+            match tokio::fs::remove_file(self.install_dir.join(path)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes `content` into `path` with `mode`, creating parent directories
+async fn write_file(path: &Path, content: &[u8], mode: u32) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(path)
+        .await?;
+    // Mode is applied on creation only, so fix leftovers of previous failed writes as well
+    file.set_permissions(Permissions::from_mode(mode)).await?;
+    file.write_all(content).await?;
+    file.sync_all().await?;
+
+    Ok(())
+}
+
+async fn check_version(path: &Path, expected_version: &str) -> Result<(), AppError> {
+    let output = tokio::process::Command::new(path)
+        .arg("--version")
+        .output()
+        .await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if output.status.success()
+        && stdout
+            .split_whitespace()
+            .any(|word| word == expected_version)
+    {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "`{} --version` returned `{}`, expected version {expected_version}",
+            path.display(),
+            stdout.trim()
+        )
+        .into())
+    }
+}
+
+/// `.<name>.<suffix>` file next to `path`
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::rpc_mock::MockRpcServer;
+    use p256::{
+        ecdsa::{signature::Signer, SigningKey},
+        pkcs8::{EncodePublicKey, LineEnding},
+    };
+    use rand::rngs::OsRng;
+    use serde_json::json;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn fake_binary(version: &str) -> Vec<u8> {
+        format!("#!/bin/sh\necho \"{BINARY_FILE_NAME} {version}\"\n").into_bytes()
+    }
+
+    fn release_archive(binary: &[u8], signature: &str) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in [
+            (format!("{BINARY_FILE_NAME}/{BINARY_FILE_NAME}"), binary),
+            (
+                format!("{BINARY_FILE_NAME}/{SIGNATURE_FILE_NAME}"),
+                signature.as_bytes(),
+            ),
+            (
+                format!("{BINARY_FILE_NAME}/{PUBLIC_KEY_FILE_NAME}"),
+                b"untrusted",
+            ),
+            (format!("{BINARY_FILE_NAME}/config/default.json"), b"{}"),
+            (
+                format!("{BINARY_FILE_NAME}/{UPDATE_SCRIPT_FILE_NAME}"),
+                b"#!/bin/sh",
+            ),
+        ] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn temp_dir() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("nop-release-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_version_status() {
+        let release = |tag_name: &str, body: &str| Release {
+            tag_name: tag_name.to_owned(),
+            body: Some(body.to_owned()),
+            assets: Vec::new(),
+        };
+        let version = |version: &str| Version::parse(version).unwrap();
+
+        let latest = release(
+            "v0.1.10",
+            "Fixes\n\n- min-supported-version: `v0.1.8`\n- Critical: true\n",
+        );
+        assert_eq!(
+            latest.metadata(),
+            ReleaseMetadata {
+                min_supported_version: Some(version("0.1.8")),
+                critical: true,
+            }
+        );
+        // Semantic ordering, not lexicographic
+        assert_eq!(
+            latest.version_status(&version("0.1.9")).unwrap(),
+            VersionStatus::Outdated {
+                latest: version("0.1.10"),
+                critical: true
+            }
+        );
+        assert_eq!(
+            latest.version_status(&version("0.1.7")).unwrap(),
+            VersionStatus::Unsupported {
+                latest: version("0.1.10"),
+                min_supported: version("0.1.8")
+            }
+        );
+        assert_eq!(
+            latest.version_status(&version("0.1.10")).unwrap(),
+            VersionStatus::UpToDate
+        );
+        assert_eq!(
+            latest.version_status(&version("0.2.0-rc.1")).unwrap(),
+            VersionStatus::UpToDate
+        );
+
+        assert_eq!(
+            release("v0.1.10", "").metadata(),
+            ReleaseMetadata::default()
+        );
+        assert!(release("latest", "").version().is_err());
+    }
+
+    #[test]
+    fn test_artifact_name() {
+        let os_release = |id: &str, version_id: &str| OsRelease {
+            id: id.to_owned(),
+            version_id: version_id.to_owned(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            artifact_name(&os_release("ubuntu", "24.04"), "x86_64").unwrap(),
+            ARTIFACT_NAME
+        );
+        assert_eq!(
+            artifact_name(&os_release("ubuntu", "20.04"), "x86_64").unwrap(),
+            ARTIFACT_NAME_OLD
+        );
+        assert_eq!(
+            artifact_name(&os_release("debian", "12"), "x86_64").unwrap(),
+            ARTIFACT_NAME
+        );
+        assert_eq!(
+            artifact_name(&os_release("debian", "11"), "x86_64").unwrap(),
+            ARTIFACT_NAME_OLD
+        );
+        assert!(artifact_name(&os_release("fedora", "40"), "x86_64").is_err());
+        assert!(artifact_name(&os_release("ubuntu", "24.04"), "aarch64").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_download_and_verify_release() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let public_key = VerifyingKey::from(&signing_key)
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        let binary = fake_binary("0.2.0");
+        let signature: Signature = signing_key.sign(&binary);
+        let signature = BASE64_STANDARD.encode(signature.to_der().as_bytes());
+
+        let server = MockRpcServer::start().await;
+        let artifact_url = server.url().join(ARTIFACT_NAME).unwrap();
+        server
+            .serve_file(
+                "/releases/latest",
+                json!({
+                    "tag_name": "v0.2.0",
+                    "assets": [{ "name": ARTIFACT_NAME, "browser_download_url": artifact_url }],
+                })
+                .to_string(),
+            )
+            .serve_file(
+                &format!("/{ARTIFACT_NAME}"),
+                release_archive(&binary, &signature),
+            );
+
+        let release = fetch_latest(&server.url().join("/releases/latest").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(release.version().unwrap(), Version::new(0, 2, 0));
+        let asset = release.asset(ARTIFACT_NAME).unwrap();
+
+        let extracted =
+            extract_bundle(&download(&asset.browser_download_url).await.unwrap()).unwrap();
+        assert_eq!(extracted.binary, binary);
+        assert_eq!(
+            extracted.files,
+            vec![
+                (PathBuf::from("config/default.json"), b"{}".to_vec()),
+                (
+                    PathBuf::from(UPDATE_SCRIPT_FILE_NAME),
+                    b"#!/bin/sh".to_vec()
+                ),
+            ]
+        );
+        assert!(verify_signature(&extracted.binary, &extracted.signature, &public_key).is_ok());
+        assert!(
+            verify_signature(&fake_binary("6.6.6"), &extracted.signature, &public_key).is_err()
+        );
+
+        let other_key = VerifyingKey::from(&SigningKey::random(&mut OsRng))
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        assert!(verify_signature(&extracted.binary, &extracted.signature, &other_key).is_err());
+
+        assert!(download(&server.url().join("/missing.zip").unwrap())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_install_bundle() {
+        let dir = temp_dir();
+        let path = dir.join(BINARY_FILE_NAME);
+        let config_path = dir.join("config/default.json");
+        let script_path = dir.join(UPDATE_SCRIPT_FILE_NAME);
+        tokio::fs::write(&path, fake_binary("0.1.0")).await.unwrap();
+        tokio::fs::create_dir_all(dir.join("config")).await.unwrap();
+        tokio::fs::write(&config_path, "old").await.unwrap();
+
+        let bundle = |version: &str, config: &str| ReleaseBundle {
+            binary: fake_binary(version),
+            signature: String::new(),
+            files: vec![
+                (PathBuf::from("config/default.json"), config.into()),
+                (
+                    PathBuf::from(UPDATE_SCRIPT_FILE_NAME),
+                    b"#!/bin/sh".to_vec(),
+                ),
+            ],
+        };
+
+        install_bundle(&path, &bundle("0.2.0", "new"), "0.2.0")
+            .await
+            .unwrap();
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), fake_binary("0.2.0"));
+        assert_eq!(
+            tokio::fs::read_to_string(&config_path).await.unwrap(),
+            "new"
+        );
+        assert_eq!(
+            tokio::fs::metadata(&script_path)
+                .await
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            EXECUTABLE_FILE_MODE
+        );
+        let backup_dir = dir.join(BACKUP_DIR_NAME);
+        assert_eq!(
+            tokio::fs::read(backup_dir.join(BINARY_FILE_NAME))
+                .await
+                .unwrap(),
+            fake_binary("0.1.0")
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(backup_dir.join("config/default.json"))
+                .await
+                .unwrap(),
+            "old"
+        );
+
+        // Binary reporting a wrong version is never installed, neither are the files
+        assert!(install_bundle(&path, &bundle("0.3.0", "newer"), "0.4.0")
+            .await
+            .is_err());
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), fake_binary("0.2.0"));
+        assert_eq!(
+            tokio::fs::read_to_string(&config_path).await.unwrap(),
+            "new"
+        );
+        assert!(!tokio::fs::try_exists(sibling_path(&path, "new"))
+            .await
+            .unwrap());
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backup_restore() {
+        let dir = temp_dir();
+        let config_path = dir.join("config/default.json");
+        let template_path = dir.join("setup_templates/new.yml");
+        tokio::fs::create_dir_all(dir.join("config")).await.unwrap();
+        tokio::fs::write(&config_path, "old").await.unwrap();
+
+        let backup = Backup::take(
+            &dir,
+            [
+                Path::new("config/default.json"),
+                Path::new("setup_templates/new.yml"),
+            ]
+            .into_iter(),
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(&config_path, "new").await.unwrap();
+        tokio::fs::create_dir_all(dir.join("setup_templates"))
+            .await
+            .unwrap();
+        tokio::fs::write(&template_path, "new").await.unwrap();
+
+        backup.restore().await.unwrap();
+        assert_eq!(
+            tokio::fs::read_to_string(&config_path).await.unwrap(),
+            "old"
+        );
+        assert!(!tokio::fs::try_exists(&template_path).await.unwrap());
+
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+}
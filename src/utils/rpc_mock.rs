@@ -0,0 +1,290 @@
+//! Minimal HTTP JSON-RPC server which stands in for a parity node in tests. It also serves
+//! static files on `GET` requests, e.g. to stand in for a release feed
+
+use alloy::primitives::Address;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+type Responses = Arc<Mutex<Vec<Response>>>;
+type Files = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// Requests a response is returned for
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Matcher {
+    /// Any call of the method
+    Method,
+    /// Call with exactly these params
+    Params(Value),
+    /// `eth_call` of the contract with input starting with the prefix, e.g. function selector
+    Call { to: Address, input_prefix: Vec<u8> },
+}
+
+struct Response {
+    method: String,
+    matcher: Matcher,
+    result: Value,
+}
+
+impl Matcher {
+    fn matches(&self, params: &Value) -> bool {
+        match self {
+            Self::Method => true,
+            Self::Params(expected) => params == expected,
+            Self::Call { to, input_prefix } => {
+                let call = &params[0];
+                let input = call["input"].as_str().or_else(|| call["data"].as_str());
+                serde_json::from_value::<Address>(call["to"].clone()).ok() == Some(*to)
+                    && input
+                        .and_then(|input| hex::decode(input.trim_start_matches("0x")).ok())
+                        .is_some_and(|input| input.starts_with(input_prefix))
+            }
+        }
+    }
+}
+
+/// Answers JSON-RPC requests with preconfigured results. Results for specific params take
+/// precedence over the ones for the whole method
+pub struct MockRpcServer {
+    address: SocketAddr,
+    responses: Responses,
+    files: Files,
+}
+
+impl MockRpcServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let responses = Responses::default();
+        let files = Files::default();
+
+        let (server_responses, server_files) = (responses.clone(), files.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(
+                    stream,
+                    server_responses.clone(),
+                    server_files.clone(),
+                ));
+            }
+        });
+
+        Self {
+            address,
+            responses,
+            files,
+        }
+    }
+
+    pub fn url(&self) -> url::Url {
+        format!("http://{}", self.address).parse().unwrap()
+    }
+
+    fn add_response(&self, method: &str, matcher: Matcher, result: Value) -> &Self {
+        let mut responses = self.responses.lock().unwrap();
+        responses.retain(|response| response.method != method || response.matcher != matcher);
+        responses.push(Response {
+            method: method.to_owned(),
+            matcher,
+            result,
+        });
+        self
+    }
+
+    /// Sets `result` returned for every subsequent `method` call
+    pub fn respond(&self, method: &str, result: Value) -> &Self {
+        self.add_response(method, Matcher::Method, result)
+    }
+
+    /// Sets `result` returned for subsequent `method` calls with exactly these `params`
+    pub fn respond_to(&self, method: &str, params: Value, result: Value) -> &Self {
+        self.add_response(method, Matcher::Params(params), result)
+    }
+
+    /// Sets ABI encoded `output` returned for subsequent `eth_call`s of the contract at `to`
+    /// whose input starts with `input_prefix`, e.g. function selector
+    pub fn respond_call(
+        &self,
+        to: Address,
+        input_prefix: impl Into<Vec<u8>>,
+        output: impl AsRef<[u8]>,
+    ) -> &Self {
+        self.add_response(
+            "eth_call",
+            Matcher::Call {
+                to,
+                input_prefix: input_prefix.into(),
+            },
+            json!(format!("0x{}", hex::encode(output))),
+        )
+    }
+
+    /// Sets block returned by `eth_getBlockByNumber` for `number` (e.g. `latest` or `0x64`),
+    /// `null` stands for a missing block
+    pub fn respond_block(&self, number: &str, block: Value) -> &Self {
+        self.respond_to("eth_getBlockByNumber", json!([number, false]), block)
+    }
+
+    /// Sets `content` returned for every subsequent `GET` request of `path`
+    pub fn serve_file(&self, path: &str, content: impl Into<Vec<u8>>) -> &Self {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_owned(), content.into());
+        self
+    }
+}
+
+async fn serve_connection(stream: TcpStream, responses: Responses, files: Files) {
+    let mut reader = BufReader::new(stream);
+
+    // Connections are kept alive by the client, so serve requests until it hangs up
+    while let Some((request_line, body)) = read_request(&mut reader).await {
+        if let Some(path) = request_line
+            .strip_prefix("GET ")
+            .and_then(|line| line.split_whitespace().next())
+        {
+            let file = files.lock().unwrap().get(path).cloned();
+            let (status, content) = match file {
+                Some(content) => ("200 OK", content),
+                None => ("404 Not Found", Vec::new()),
+            };
+            if write_response(&mut reader, status, "application/octet-stream", &content)
+                .await
+                .is_err()
+            {
+                return;
+            }
+            continue;
+        }
+
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Array(requests)) => Value::Array(
+                requests
+                    .iter()
+                    .map(|request| handle_request(request, &responses))
+                    .collect(),
+            ),
+            Ok(request) => handle_request(&request, &responses),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": e.to_string() },
+            }),
+        }
+        .to_string();
+
+        if write_response(
+            &mut reader,
+            "200 OK",
+            "application/json",
+            response.as_bytes(),
+        )
+        .await
+        .is_err()
+        {
+            return;
+        }
+    }
+}
+
+async fn write_response(
+    reader: &mut BufReader<TcpStream>,
+    status: &str,
+    content_type: &str,
+    content: &[u8],
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n",
+        content.len()
+    );
+    let stream = reader.get_mut();
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(content).await
+}
+
+/// Reads request line and body of the next request on the connection
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Option<(String, Vec<u8>)> {
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).await.ok()? == 0 {
+        return None;
+    }
+    let mut content_length = 0;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.ok()? == 0 {
+            return None;
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await.ok()?;
+    Some((request_line.trim_end().to_owned(), body))
+}
+
+/// Block with the fields parity returns, enough for alloy to deserialize it
+pub fn block(number: u64, hash: u8, timestamp: u64) -> Value {
+    json!({
+        "number": format!("{number:#x}"),
+        "hash": format!("0x{}", hex::encode([hash; 32])),
+        "parentHash": format!("0x{}", hex::encode([0; 32])),
+        "sha3Uncles": format!("0x{}", hex::encode([0; 32])),
+        "miner": format!("0x{}", hex::encode([0; 20])),
+        "stateRoot": format!("0x{}", hex::encode([0; 32])),
+        "transactionsRoot": format!("0x{}", hex::encode([0; 32])),
+        "receiptsRoot": format!("0x{}", hex::encode([0; 32])),
+        "logsBloom": format!("0x{}", hex::encode([0; 256])),
+        "difficulty": "0x1",
+        "gasLimit": "0x7a1200",
+        "gasUsed": "0x0",
+        "timestamp": format!("{timestamp:#x}"),
+        "extraData": "0x",
+        "mixHash": format!("0x{}", hex::encode([0; 32])),
+        "nonce": "0x0000000000000000",
+        "size": "0x24b",
+        "uncles": [],
+        "transactions": [],
+    })
+}
+
+fn handle_request(request: &Value, responses: &Responses) -> Value {
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = &request["params"];
+
+    let responses = responses.lock().unwrap();
+    let matching = responses
+        .iter()
+        .filter(|response| response.method == method && response.matcher.matches(params));
+    let result = matching
+        .clone()
+        .find(|response| response.matcher != Matcher::Method)
+        .or_else(|| matching.clone().next())
+        .map(|response| &response.result);
+
+    match result {
+        Some(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32601, "message": format!("Method not found: {method}") },
+        }),
+    }
+}
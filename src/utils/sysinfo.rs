@@ -0,0 +1,293 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use crate::error::AppError;
+
+const PROC_PATH: &str = "/proc";
+const OS_RELEASE_PATH: &str = "/etc/os-release";
+/// Used if the page size can't be queried
+const DEFAULT_PAGE_SIZE_KB: u64 = 4;
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OsRelease {
+    pub id: String,
+    pub version_id: String,
+    pub pretty_name: String,
+    pub kernel: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryInfo {
+    pub total_kb: u64,
+    pub free_kb: u64,
+    pub available_kb: u64,
+    pub swap_total_kb: u64,
+    pub swap_free_kb: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub total_inodes: u64,
+    pub available_inodes: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub state: char,
+    pub rss_kb: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerStats {
+    #[serde(rename(deserialize = "Name"))]
+    pub name: String,
+    #[serde(rename(deserialize = "CPUPerc"))]
+    pub cpu_percent: String,
+    #[serde(rename(deserialize = "MemUsage"))]
+    pub memory_usage: String,
+    #[serde(rename(deserialize = "MemPerc"))]
+    pub memory_percent: String,
+    #[serde(rename(deserialize = "NetIO"))]
+    pub net_io: String,
+    #[serde(rename(deserialize = "BlockIO"))]
+    pub block_io: String,
+}
+
+impl MemoryInfo {
+    pub fn used_kb(&self) -> u64 {
+        self.total_kb.saturating_sub(self.available_kb)
+    }
+}
+
+pub fn os_release() -> Result<OsRelease, AppError> {
+    let mut os_release = parse_os_release(&std::fs::read_to_string(OS_RELEASE_PATH)?);
+    os_release.kernel = std::fs::read_to_string(Path::new(PROC_PATH).join("sys/kernel/osrelease"))?
+        .trim()
+        .to_owned();
+    Ok(os_release)
+}
+
+pub fn memory_info() -> Result<MemoryInfo, AppError> {
+    parse_meminfo(&std::fs::read_to_string(
+        Path::new(PROC_PATH).join("meminfo"),
+    )?)
+}
+
+pub fn load_average() -> Result<LoadAverage, AppError> {
+    parse_loadavg(&std::fs::read_to_string(
+        Path::new(PROC_PATH).join("loadavg"),
+    )?)
+}
+
+pub fn uptime() -> Result<Duration, AppError> {
+    let text = std::fs::read_to_string(Path::new(PROC_PATH).join("uptime"))?;
+    let secs = text
+        .split_whitespace()
+        .next()
+        .and_then(|secs| secs.parse::<f64>().ok())
+        .ok_or_else(|| anyhow!("Unexpected /proc/uptime format: {text}"))?;
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// Disk space and inodes of the filesystem containing `path`
+#[allow(clippy::unnecessary_cast)]
+pub fn disk_usage(path: &Path) -> Result<DiskUsage, AppError> {
+    let stat = nix::sys::statvfs::statvfs(path).map_err(std::io::Error::from)?;
+    let fragment_size = stat.fragment_size() as u64;
+
+    Ok(DiskUsage {
+        path: path.to_owned(),
+        total_bytes: (stat.blocks() as u64).saturating_mul(fragment_size),
+        available_bytes: (stat.blocks_available() as u64).saturating_mul(fragment_size),
+        total_inodes: stat.files() as u64,
+        available_inodes: stat.files_available() as u64,
+    })
+}
+
+pub fn processes() -> Result<Vec<ProcessInfo>, AppError> {
+    let page_size_kb = nix::unistd::sysconf(nix::unistd::SysconfVar::PAGE_SIZE)
+        .ok()
+        .flatten()
+        .map_or(DEFAULT_PAGE_SIZE_KB, |page_size| page_size as u64 / 1024);
+    let mut processes = std::fs::read_dir(PROC_PATH)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            entry.file_name().to_str()?.parse::<u32>().ok()?;
+
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            let rss_pages = std::fs::read_to_string(entry.path().join("statm"))
+                .ok()
+                .and_then(|statm| statm.split_whitespace().nth(1)?.parse::<u64>().ok())
+                .unwrap_or_default();
+
+            let mut process = parse_process_stat(&stat)?;
+            process.rss_kb = rss_pages * page_size_kb;
+            Some(process)
+        })
+        .collect::<Vec<_>>();
+
+    processes.sort_by_key(|process| process.pid);
+    Ok(processes)
+}
+
+/// Resource usage of running containers as reported by `docker stats`
+pub fn container_stats() -> Vec<ContainerStats> {
+    Command::new("docker")
+        .arg("stats")
+        .arg("--no-stream")
+        .arg("--format={{ json . }}")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|text| {
+            text.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_os_release(text: &str) -> OsRelease {
+    let values = text
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+        .collect::<HashMap<_, _>>();
+    let value = |key: &str| values.get(key).copied().unwrap_or_default().to_owned();
+
+    OsRelease {
+        id: value("ID"),
+        version_id: value("VERSION_ID"),
+        pretty_name: value("PRETTY_NAME"),
+        kernel: String::new(),
+    }
+}
+
+fn parse_meminfo(text: &str) -> Result<MemoryInfo, AppError> {
+    let values = text
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((
+                key.trim(),
+                value.split_whitespace().next()?.parse::<u64>().ok()?,
+            ))
+        })
+        .collect::<HashMap<_, _>>();
+    let value = |key: &str| {
+        values
+            .get(key)
+            .copied()
+            .ok_or_else(|| anyhow!("`{key}` is missing in /proc/meminfo"))
+    };
+
+    Ok(MemoryInfo {
+        total_kb: value("MemTotal")?,
+        free_kb: value("MemFree")?,
+        available_kb: value("MemAvailable")?,
+        swap_total_kb: value("SwapTotal").unwrap_or_default(),
+        swap_free_kb: value("SwapFree").unwrap_or_default(),
+    })
+}
+
+fn parse_loadavg(text: &str) -> Result<LoadAverage, AppError> {
+    let mut values = text
+        .split_whitespace()
+        .take(3)
+        .map(|value| value.parse::<f64>());
+
+    match (values.next(), values.next(), values.next()) {
+        (Some(Ok(one)), Some(Ok(five)), Some(Ok(fifteen))) => {
+            Ok(LoadAverage { one, five, fifteen })
+        }
+        _ => Err(anyhow!("Unexpected /proc/loadavg format: {text}").into()),
+    }
+}
+
+/// Parses `/proc/<pid>/stat`. Process name is enclosed in parentheses and may contain spaces
+fn parse_process_stat(text: &str) -> Option<ProcessInfo> {
+    let (pid, rest) = text.split_once(" (")?;
+    let (name, rest) = rest.rsplit_once(") ")?;
+    let mut fields = rest.split_whitespace();
+
+    Some(ProcessInfo {
+        pid: pid.trim().parse().ok()?,
+        state: fields.next()?.chars().next()?,
+        ppid: fields.next()?.parse().ok()?,
+        name: name.to_owned(),
+        rss_kb: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        assert_eq!(
+            parse_os_release(
+                "PRETTY_NAME=\"Ubuntu 22.04.4 LTS\"\nNAME=\"Ubuntu\"\nVERSION_ID=\"22.04\"\nID=ubuntu\n"
+            ),
+            OsRelease {
+                id: "ubuntu".to_owned(),
+                version_id: "22.04".to_owned(),
+                pretty_name: "Ubuntu 22.04.4 LTS".to_owned(),
+                kernel: String::new(),
+            }
+        );
+
+        let memory = parse_meminfo(
+            "MemTotal:       16318412 kB\nMemFree:         1042356 kB\nMemAvailable:    8123456 kB\nBuffers:          123456 kB\nSwapTotal:       2097148 kB\nSwapFree:        2097148 kB\n",
+        )
+        .unwrap();
+        assert_eq!(memory.total_kb, 16318412);
+        assert_eq!(memory.used_kb(), 16318412 - 8123456);
+        assert!(parse_meminfo("MemTotal: 1 kB\n").is_err());
+
+        assert_eq!(
+            parse_loadavg("0.52 0.58 0.59 1/1034 123456\n").unwrap(),
+            LoadAverage {
+                one: 0.52,
+                five: 0.58,
+                fifteen: 0.59
+            }
+        );
+
+        assert_eq!(
+            parse_process_stat("1234 (tokio worker) S 1 1234 1234 0 -1 4194560 1234"),
+            Some(ProcessInfo {
+                pid: 1234,
+                ppid: 1,
+                name: "tokio worker".to_owned(),
+                state: 'S',
+                rss_kb: 0,
+            })
+        );
+    }
+}
@@ -1,8 +1,11 @@
 #!/usr/bin/env bash
 cd "$( dirname "$(readlink -f "${BASH_SOURCE[0]}")" )"
-if [[ -d /etc/cron.daily ]]; then
-  rm -f /etc/cron.daily/airdao-nop-rs
-  ln -fs $PWD/update.sh /etc/cron.daily/airdao-nop-rs
+# Runs hourly, so scheduled update gets into the configured maintenance window.
+# Update does nothing unless there are changes to apply
+rm -f /etc/cron.daily/airdao-nop-rs
+if [[ -d /etc/cron.hourly ]]; then
+  rm -f /etc/cron.hourly/airdao-nop-rs
+  ln -fs $PWD/update.sh /etc/cron.hourly/airdao-nop-rs
 fi
 
 cat > /etc/sysctl.d/10-airdao.conf <<-END
@@ -12,50 +15,12 @@ sysctl -p /etc/sysctl.d/10-airdao.conf
 
 cd ~/airdao-nop-rs
 
-CURRENT_VERSION=$(./airdao-nop-rs --version | cut -d ' ' -f2)
-LATEST_TAG=$(curl -s https://raw.githubusercontent.com/ambrosus/airdao-nop-rs/main/Cargo.toml | grep '^version' | sed -E 's/version = "(.*)"/\1/')
-DIR_NAME="airdao-nop-rs"
-ZIP_FILE="${DIR_NAME}.zip"
+# Release feed outage must not block node updates, they run with the installed version then
+./airdao-nop-rs self-update || echo "Self-update failed, updating nodes with the installed version" >&2
 
-install_airdao() {
-    cd ~
+./airdao-nop-rs update --scheduled
 
-    curl -L -o "$ZIP_FILE" "$FILE_URL" || { echo "Failed to download file"; exit 1; }
-
-    unzip -o "$ZIP_FILE" || { echo "Failed to unzip file"; exit 1; }
-    rm "$ZIP_FILE"
-    cd "$DIR_NAME"
-
-    if cosign verify-blob --key airdao-nop-rs.pub --signature airdao-nop-rs.sig airdao-nop-rs; then
-        echo -e "\033[0;32mVerified OK\033[0m"
-    else
-        rm -rf ~/airdao-nop-rs 
-        echo -e "\033[0;31mError: Verification failed\033[0m"
-        exit 1
-    fi
-
-    chmod +x ./airdao-nop-rs
-
-    ./airdao-nop-rs update
-}
-
-if [[ "$CURRENT_VERSION" != "$LATEST_TAG" ]]; then
-    DISTRO_NAME=$(lsb_release -i | cut -d ':' -f 2 | xargs)
-    MAJOR_VERSION=$(lsb_release -sr | cut -d '.' -f 1)
-    if [[ "$DISTRO_NAME" == "Ubuntu" ]]; then
-        if (( MAJOR_VERSION >= 22 )); then
-            FILE_URL="https://github.com/ambrosus/airdao-nop-rs/releases/download/v$LATEST_TAG/airdao-nop-rs-x86-64.zip"
-        else
-            FILE_URL="https://github.com/ambrosus/airdao-nop-rs/releases/download/v$LATEST_TAG/airdao-nop-rs-x86-64-old.zip"
-        fi
-    elif [[ "$DISTRO_NAME" == "Debian" ]]; then
-        if (( MAJOR_VERSION > 11 )); then
-            FILE_URL="https://github.com/ambrosus/airdao-nop-rs/releases/download/v$LATEST_TAG/airdao-nop-rs-x86-64.zip"
-        else
-            FILE_URL="https://github.com/ambrosus/airdao-nop-rs/releases/download/v$LATEST_TAG/airdao-nop-rs-x86-64-old.zip"
-        fi
-    else
-        exit 1
-    fi
-    install_airdao
-fi
+for state in profiles/*/state.json; do
+  [[ -f "$state" ]] || continue
+  ./airdao-nop-rs update --scheduled --profile "$(basename "$(dirname "$state")")"
+done